@@ -22,38 +22,199 @@ fn field_name(field: &Field, index: u32, span: Span) -> Member {
     field_name
 }
 
-// gen_spec_fun(span, is_closed, fields);
+/// A single field's declaration-order `Member` together with any `#[verus(..)]` annotations
+/// controlling how it participates in the generated comparisons.
+pub(crate) struct FieldInfo {
+    pub member: Member,
+    pub ty: syn::Type,
+    /// `#[verus(skip)]`: omit this field from both equality and ordering entirely, e.g. for
+    /// cache or phantom fields that shouldn't affect comparison.
+    pub skip: bool,
+    /// `#[verus(ord_key = N)]`: compare this field at priority `N` instead of its declaration
+    /// index. Fields without the attribute keep their declaration index as their priority.
+    pub ord_key: Option<i64>,
+}
+
+/// Parses the `#[verus(skip)]` / `#[verus(ord_key = N)]` helper attributes off of one field.
+fn parse_field_attrs(field: &Field) -> (bool, Option<i64>) {
+    let mut skip = false;
+    let mut ord_key = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("verus") {
+            continue;
+        }
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else if meta.path.is_ident("ord_key") {
+                let lit: syn::LitInt = meta.value()?.parse()?;
+                ord_key = Some(lit.base10_parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unrecognized verus(..) field attribute"))
+            }
+        });
+    }
+    (skip, ord_key)
+}
+
+/// The fields of a single struct, or of a single enum variant, in declaration order.
+pub(crate) struct VariantFields {
+    /// `None` for a plain struct; `Some(variant_ident)` for an enum variant.
+    pub variant: Option<Ident>,
+    pub fields: Vec<FieldInfo>,
+}
+
+impl VariantFields {
+    /// Fields that participate in equality/ordering, in declaration order (`#[verus(skip)]`
+    /// fields removed).
+    fn compared_indices(&self) -> Vec<usize> {
+        self.fields.iter().enumerate().filter(|(_, f)| !f.skip).map(|(i, _)| i).collect()
+    }
+
+    /// Like `compared_indices`, but reordered by `#[verus(ord_key)]` (falling back to
+    /// declaration index for fields that don't specify one, with ties broken by declaration
+    /// order).
+    fn ordering_indices(&self) -> Vec<usize> {
+        let mut indices = self.compared_indices();
+        indices.sort_by_key(|&i| (self.fields[i].ord_key.unwrap_or(i as i64), i));
+        indices
+    }
+
+    /// Types of the fields that actually get compared (see `compared_indices`); these are
+    /// exactly the types whose spec-trait impl the generated body calls into.
+    fn compared_types(&self) -> impl Iterator<Item = &syn::Type> {
+        self.compared_indices().into_iter().map(move |i| &self.fields[i].ty)
+    }
+}
+
+/// Everything a `gen_spec_fun` needs to know about the shape of the derive target:
+/// either the single field list of a struct, or the field lists of each variant of an enum,
+/// in top-to-bottom declaration order.
+pub(crate) enum Shape {
+    Struct(VariantFields),
+    Enum(Vec<VariantFields>),
+}
+
+fn collect_fields(fields: &syn::Fields, closed: &mut bool) -> Vec<FieldInfo> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            if matches!(&field.vis, Visibility::Restricted(_) | Visibility::Inherited) {
+                *closed = true;
+            }
+            let (skip, ord_key) = parse_field_attrs(field);
+            FieldInfo {
+                member: field_name(field, i as u32, field.span()),
+                ty: field.ty.clone(),
+                skip,
+                ord_key,
+            }
+        })
+        .collect()
+}
+
+/// Walks a type's tokens (recursing into any bracket/paren/brace groups) looking for a
+/// mention of one of `params` — a cheap stand-in for a full type-parameter-usage visitor,
+/// good enough to decide whether a field needs a spec-trait bound.
+fn tokens_mention_any(tokens: proc_macro2::TokenStream, params: &std::collections::HashSet<String>) -> bool {
+    tokens.into_iter().any(|tt| match tt {
+        proc_macro2::TokenTree::Ident(ident) => params.contains(&ident.to_string()),
+        proc_macro2::TokenTree::Group(group) => tokens_mention_any(group.stream(), params),
+        _ => false,
+    })
+}
+
+/// Collects the field types of `shape` (across every variant, for enums) that mention one of
+/// the type's own generic parameters, deduplicated by their rendered tokens and in first-seen
+/// order. These are exactly the types that need a `FieldTy: #spec_trait<FieldTy>` bound for the
+/// generated body (which calls `FieldTy`'s own spec-trait method) to type-check.
+fn generic_field_types<'a>(
+    shape: &'a Shape,
+    type_params: &std::collections::HashSet<String>,
+) -> Vec<&'a syn::Type> {
+    let variant_fields: Vec<&VariantFields> = match shape {
+        Shape::Struct(sf) => vec![sf],
+        Shape::Enum(variants) => variants.iter().collect(),
+    };
+    let mut seen = std::collections::HashSet::new();
+    let mut result = Vec::new();
+    for vf in variant_fields {
+        for ty in vf.compared_types() {
+            if tokens_mention_any(ty.to_token_stream(), type_params)
+                && seen.insert(ty.to_token_stream().to_string())
+            {
+                result.push(ty);
+            }
+        }
+    }
+    result
+}
+
+// gen_spec_fun(span, is_closed, shape);
 pub(crate) fn spec_trait_expand_for_struct<Crate: ToTokens>(
     input: proc_macro::TokenStream,
     crat: Crate,
     trait_name: &str,
-    gen_spec_fun: fn(Span, &dyn ToTokens, Vec<&dyn ToTokens>) -> proc_macro2::TokenStream,
+    gen_spec_fun: fn(Span, &dyn ToTokens, Shape) -> proc_macro2::TokenStream,
 ) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as syn::DeriveInput);
     let span = input.span();
     let name = input.ident;
     let generics = input.generics;
+    let type_params: std::collections::HashSet<String> = generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            syn::GenericParam::Type(t) => Some(t.ident.to_string()),
+            _ => None,
+        })
+        .collect();
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let spec_trait = Ident::new(trait_name, span);
-    let s = match input.data {
-        syn::Data::Struct(s) => s,
-        _ => panic!("SpecTrait derive macro only support struct"),
-    };
-    let mut fields = vec![];
     let mut closed = false;
-    for (i, field) in s.fields.iter().enumerate() {
-        fields.push(field_name(&field, i as u32, field.span()));
-        if matches!(&field.vis, Visibility::Restricted(_) | Visibility::Inherited) {
-            closed = true;
+    let shape = match input.data {
+        syn::Data::Struct(s) => {
+            let fields = collect_fields(&s.fields, &mut closed);
+            Shape::Struct(VariantFields { variant: None, fields })
         }
-    }
+        syn::Data::Enum(e) => {
+            let variants = e
+                .variants
+                .iter()
+                .map(|variant| VariantFields {
+                    variant: Some(variant.ident.clone()),
+                    fields: collect_fields(&variant.fields, &mut closed),
+                })
+                .collect();
+            Shape::Enum(variants)
+        }
+        syn::Data::Union(_) => panic!("SpecTrait derive macro does not support unions"),
+    };
     let closed_or_open = if closed {
         quote_spanned! {span => closed}
     } else {
         quote_spanned! {span => open}
     };
-    let spec_func_def =
-        gen_spec_fun(span, &closed_or_open, fields.iter().map(|v| v as &dyn ToTokens).collect());
+
+    // Like rustc's own `derive`, which adds a `T: PartialOrd` bound per type parameter, require
+    // each field type that mentions a generic parameter to implement the trait we're deriving,
+    // so e.g. `self.0.spec_partial_cmp(...)` type-checks for a generic `struct Pair<T>(T, T)`.
+    let extra_bounds: Vec<_> = generic_field_types(&shape, &type_params)
+        .into_iter()
+        .map(|ty| quote_spanned! {span => #ty: #crat::#spec_trait<#ty> })
+        .collect();
+    let where_clause = if where_clause.is_some() || !extra_bounds.is_empty() {
+        let existing: Vec<_> =
+            where_clause.map(|wc| wc.predicates.iter().collect()).unwrap_or_else(Vec::new);
+        quote_spanned! {span => where #(#existing,)* #(#extra_bounds,)* }
+    } else {
+        quote! {}
+    };
+
+    let spec_func_def = gen_spec_fun(span, &closed_or_open, shape);
     let expand = quote_spanned! { span =>
         verus!{
             impl #impl_generics #crat::#spec_trait<#name #ty_generics> for #name #ty_generics #where_clause {
@@ -64,42 +225,335 @@ pub(crate) fn spec_trait_expand_for_struct<Crate: ToTokens>(
     proc_macro::TokenStream::from(expand)
 }
 
+/// Binds one fresh identifier per field of a variant (including any `#[verus(skip)]` fields,
+/// since the match pattern still needs to destructure them), used to destructure both sides of
+/// a `match (self, rhs)` without colliding with the field names themselves.
+fn bind_idents(fields: &[FieldInfo], prefix: &str) -> Vec<Ident> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, _)| Ident::new(&format!("__verus_derive_{}_{}", prefix, i), Span::call_site()))
+        .collect()
+}
+
+/// Builds the pattern `Self::Variant { f0: b0, f1: b1, .. }` / `Self::Variant(b0, b1, ..)` /
+/// `Self::Variant` matching *all* of a variant's fields against freshly bound identifiers.
+fn variant_pattern(
+    variant: &Ident,
+    fields: &[FieldInfo],
+    bindings: &[Ident],
+) -> proc_macro2::TokenStream {
+    if fields.is_empty() {
+        quote! { Self::#variant }
+    } else if matches!(fields[0].member, Member::Named(_)) {
+        let names = fields.iter().map(|f| match &f.member {
+            Member::Named(name) => name,
+            Member::Unnamed(_) => unreachable!("mixed named/unnamed fields in one variant"),
+        });
+        quote! { Self::#variant { #(#names: #bindings),* } }
+    } else {
+        quote! { Self::#variant(#(#bindings),*) }
+    }
+}
+
+/// Builds the pattern `Variant { .. }` / `Variant(..)` / `Variant` used just to test which
+/// variant a value belongs to, without binding its fields.
+fn wildcard_variant_pattern(variant: &Ident, fields: &[FieldInfo]) -> proc_macro2::TokenStream {
+    if fields.is_empty() {
+        quote! { Self::#variant }
+    } else if matches!(fields[0].member, Member::Named(_)) {
+        quote! { Self::#variant { .. } }
+    } else {
+        quote! { Self::#variant(..) }
+    }
+}
+
+/// Shared by `spec_partial_eq_expand` and `spec_eq_expand`: ANDs the per-field `field_method`
+/// (`spec_partial_eq` or `spec_eq`) across the fields selected by `compared_indices`, in
+/// declaration order, matching the corresponding variant first for enums.
+fn eq_like_expand(
+    span: Span,
+    closed_or_open: &dyn ToTokens,
+    shape: Shape,
+    fn_name: &Ident,
+    field_method: &Ident,
+) -> proc_macro2::TokenStream {
+    match shape {
+        Shape::Struct(sf) => {
+            let indices = sf.compared_indices();
+            let lhs: Vec<_> = indices.iter().map(|&i| &sf.fields[i].member).collect();
+            let rhs = lhs.clone();
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self, rhs: &Self) -> bool
+                {
+                    true #(&& self.#lhs.#field_method(&rhs.#rhs))*
+                }
+            }
+        }
+        Shape::Enum(variants) => {
+            let arms = variants.iter().map(|v| {
+                let variant = v.variant.as_ref().expect("enum variant");
+                let lhs_all = bind_idents(&v.fields, "lhs");
+                let rhs_all = bind_idents(&v.fields, "rhs");
+                let lhs_pat = variant_pattern(variant, &v.fields, &lhs_all);
+                let rhs_pat = variant_pattern(variant, &v.fields, &rhs_all);
+                let indices = v.compared_indices();
+                let lhs: Vec<_> = indices.iter().map(|&i| &lhs_all[i]).collect();
+                let rhs: Vec<_> = indices.iter().map(|&i| &rhs_all[i]).collect();
+                quote_spanned! {span =>
+                    (#lhs_pat, #rhs_pat) => true #(&& #lhs.#field_method(&#rhs))*,
+                }
+            });
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self, rhs: &Self) -> bool
+                {
+                    match (self, rhs) {
+                        #(#arms)*
+                        _ => false,
+                    }
+                }
+            }
+        }
+    }
+}
+
 // When usig derived(PartialEq)
 // spec_partial_eq  == builtin::spec_eq
 pub(crate) fn spec_partial_eq_expand(
     span: Span,
     closed_or_open: &dyn ToTokens,
-    fields: Vec<&dyn ToTokens>,
+    shape: Shape,
 ) -> proc_macro2::TokenStream {
-    let ret = quote_spanned! {span =>
-        #closed_or_open spec fn spec_partial_eq(&self, rhs: &Self) -> bool
-        {
-            true #(&& self.#fields.spec_partial_eq(&rhs.#fields))*
-        }
-    };
-    ret
+    let fn_name = Ident::new("spec_partial_eq", span);
+    eq_like_expand(span, closed_or_open, shape, &fn_name, &fn_name)
 }
 
-/// It will produce a lexicographic ordering based on the top-to-bottom declaration order of the struct’s members
-pub(crate) fn spec_partial_ord_expand(
+/// `SpecEq` derive: the total-equality counterpart of `spec_partial_eq_expand`, ANDing each
+/// field's `spec_eq` in declaration order (matching the corresponding variant first for enums).
+pub(crate) fn spec_eq_expand(
     span: Span,
     closed_or_open: &dyn ToTokens,
-    fields: Vec<&dyn ToTokens>,
+    shape: Shape,
 ) -> proc_macro2::TokenStream {
-    quote_spanned! {span =>
-        #closed_or_open spec fn spec_partial_cmp(&self, rhs: &Self) -> Option<core::cmp::Ordering>
-        {
-            if false {
-                None
+    let fn_name = Ident::new("spec_eq", span);
+    eq_like_expand(span, closed_or_open, shape, &fn_name, &fn_name)
+}
+
+/// Folds `fields` (each field's own `spec_hash()`, in declaration order) into a single `int`
+/// accumulator via Horner's method, starting from `0`.
+fn fold_spec_hash(span: Span, fields: &[proc_macro2::TokenStream]) -> proc_macro2::TokenStream {
+    fields.iter().fold(quote_spanned! {span => 0int}, |acc, field| {
+        quote_spanned! {span => (#acc * 31 + #field.spec_hash()) }
+    })
+}
+
+/// `SpecHash` derive: folds each field's `spec_hash` into a single `int` accumulator, in
+/// declaration order. This walks the *exact same* field set as `eq_like_expand` (by going
+/// through `compared_indices`, the same helper `spec_partial_eq_expand`/`spec_eq_expand` use,
+/// `#[verus(skip)]` included) so that spec-equal values are guaranteed to hash equal.
+pub(crate) fn spec_hash_expand(
+    span: Span,
+    closed_or_open: &dyn ToTokens,
+    shape: Shape,
+) -> proc_macro2::TokenStream {
+    let fn_name = Ident::new("spec_hash", span);
+    match shape {
+        Shape::Struct(sf) => {
+            let indices = sf.compared_indices();
+            let fields: Vec<_> = indices
+                .iter()
+                .map(|&i| {
+                    let m = &sf.fields[i].member;
+                    quote! { self.#m }
+                })
+                .collect();
+            let body = fold_spec_hash(span, &fields);
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self) -> int
+                {
+                    #body
+                }
+            }
+        }
+        Shape::Enum(variants) => {
+            let arms = variants.iter().enumerate().map(|(i, v)| {
+                let variant = v.variant.as_ref().expect("enum variant");
+                let binds = bind_idents(&v.fields, "h");
+                let pat = variant_pattern(variant, &v.fields, &binds);
+                let indices = v.compared_indices();
+                let fields: Vec<_> =
+                    indices.iter().map(|&i| binds[i].to_token_stream()).collect();
+                let discr = i as isize;
+                let body = fold_spec_hash(span, &fields);
+                quote_spanned! {span =>
+                    #pat => (#discr * 31 + #body),
+                }
+            });
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self) -> int
+                {
+                    match self {
+                        #(#arms)*
+                    }
+                }
             }
-            #(
-            else if self.#fields.spec_partial_cmp(&rhs.#fields) != Some(core::cmp::Ordering::Equal) {
-                self.#fields.spec_partial_cmp(&rhs.#fields)
+        }
+    }
+}
+
+/// Shared by `spec_partial_ord_expand` and `spec_ord_expand`: builds the lexicographic chain
+/// `if false { <equal>) } else if lhs[0] `not_equal` rhs[0] { lhs[0] `cmp` rhs[0] } else if ...
+/// else { <equal> }`, comparing fields via `cmp_method` (`spec_partial_cmp` or `spec_cmp`).
+fn lexicographic_cmp_chain(
+    span: Span,
+    lhs: &[proc_macro2::TokenStream],
+    rhs: &[proc_macro2::TokenStream],
+    cmp_method: &Ident,
+    equal: &proc_macro2::TokenStream,
+    not_equal: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote_spanned! {span =>
+        if false {
+            #equal
+        }
+        #(
+        else if #lhs.#cmp_method(&#rhs) != #not_equal {
+            #lhs.#cmp_method(&#rhs)
+        }
+        )*
+        else {
+            #equal
+        }
+    }
+}
+
+/// Shared by `spec_partial_ord_expand` and `spec_ord_expand`: orders by the top-to-bottom
+/// declaration index of the variant first (via `fn_name`'s own `self_discr`/`rhs_discr`
+/// prelude), and only when two values agree on the discriminant falls through to a
+/// field-by-field lexicographic comparison ordered by `ordering_indices`.
+fn ord_like_expand(
+    span: Span,
+    closed_or_open: &dyn ToTokens,
+    shape: Shape,
+    fn_name: &Ident,
+    ret_typ: &proc_macro2::TokenStream,
+    cmp_method: &Ident,
+    equal: &proc_macro2::TokenStream,
+    not_equal: &proc_macro2::TokenStream,
+    less: &proc_macro2::TokenStream,
+    greater: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    match shape {
+        Shape::Struct(sf) => {
+            let indices = sf.ordering_indices();
+            let lhs: Vec<_> = indices
+                .iter()
+                .map(|&i| {
+                    let m = &sf.fields[i].member;
+                    quote! { self.#m }
+                })
+                .collect();
+            let rhs: Vec<_> = indices
+                .iter()
+                .map(|&i| {
+                    let m = &sf.fields[i].member;
+                    quote! { rhs.#m }
+                })
+                .collect();
+            let body = lexicographic_cmp_chain(span, &lhs, &rhs, cmp_method, equal, not_equal);
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self, rhs: &Self) -> #ret_typ
+                {
+                    #body
+                }
             }
-            )*
-            else {
-                Some(core::cmp::Ordering::Equal)
+        }
+        Shape::Enum(variants) => {
+            let discr_arms = variants.iter().enumerate().map(|(i, v)| {
+                let variant = v.variant.as_ref().expect("enum variant");
+                let wildcard = wildcard_variant_pattern(variant, &v.fields);
+                let i = i as isize;
+                quote_spanned! {span => #wildcard => #i, }
+            });
+            let field_arms = variants.iter().map(|v| {
+                let variant = v.variant.as_ref().expect("enum variant");
+                let lhs_all = bind_idents(&v.fields, "lhs");
+                let rhs_all = bind_idents(&v.fields, "rhs");
+                let lhs_pat = variant_pattern(variant, &v.fields, &lhs_all);
+                let rhs_pat = variant_pattern(variant, &v.fields, &rhs_all);
+                let indices = v.ordering_indices();
+                let lhs: Vec<_> = indices.iter().map(|&i| lhs_all[i].to_token_stream()).collect();
+                let rhs: Vec<_> = indices.iter().map(|&i| rhs_all[i].to_token_stream()).collect();
+                let body = lexicographic_cmp_chain(span, &lhs, &rhs, cmp_method, equal, not_equal);
+                quote_spanned! {span =>
+                    (#lhs_pat, #rhs_pat) => { #body },
+                }
+            });
+            quote_spanned! {span =>
+                #closed_or_open spec fn #fn_name(&self, rhs: &Self) -> #ret_typ
+                {
+                    let self_discr: int = match self { #(#discr_arms)* };
+                    let rhs_discr: int = match rhs { #(#discr_arms)* };
+                    if self_discr != rhs_discr {
+                        if self_discr < rhs_discr {
+                            #less
+                        } else {
+                            #greater
+                        }
+                    } else {
+                        match (self, rhs) {
+                            #(#field_arms)*
+                            // self_discr == rhs_discr implies self and rhs are the same variant,
+                            // so this arm is unreachable; it exists only for exhaustiveness.
+                            _ => #equal,
+                        }
+                    }
+                }
             }
         }
     }
 }
+
+/// It will produce a lexicographic ordering over the compared fields in `ordering_indices` order
+/// (declaration order unless `#[verus(ord_key = N)]` overrides it); for enums, values are first
+/// ordered by the declaration index of their variant
+pub(crate) fn spec_partial_ord_expand(
+    span: Span,
+    closed_or_open: &dyn ToTokens,
+    shape: Shape,
+) -> proc_macro2::TokenStream {
+    ord_like_expand(
+        span,
+        closed_or_open,
+        shape,
+        &Ident::new("spec_partial_cmp", span),
+        &quote! { Option<core::cmp::Ordering> },
+        &Ident::new("spec_partial_cmp", span),
+        &quote! { Some(core::cmp::Ordering::Equal) },
+        &quote! { Some(core::cmp::Ordering::Equal) },
+        &quote! { Some(core::cmp::Ordering::Less) },
+        &quote! { Some(core::cmp::Ordering::Greater) },
+    )
+}
+
+/// `SpecOrd` derive: the total-order analogue of `spec_partial_ord_expand`. Returns a bare
+/// `core::cmp::Ordering` by lexicographically comparing each field's `spec_cmp`, so callers
+/// whose fields are all totally ordered don't have to unwrap `Option<Ordering>` themselves.
+pub(crate) fn spec_ord_expand(
+    span: Span,
+    closed_or_open: &dyn ToTokens,
+    shape: Shape,
+) -> proc_macro2::TokenStream {
+    ord_like_expand(
+        span,
+        closed_or_open,
+        shape,
+        &Ident::new("spec_cmp", span),
+        &quote! { core::cmp::Ordering },
+        &Ident::new("spec_cmp", span),
+        &quote! { core::cmp::Ordering::Equal },
+        &quote! { core::cmp::Ordering::Equal },
+        &quote! { core::cmp::Ordering::Less },
+        &quote! { core::cmp::Ordering::Greater },
+    )
+}
@@ -23,8 +23,8 @@ use crate::sst_to_air_func::{func_bind, func_bind_trig, func_def_args};
 use crate::util::vec_map;
 use air::ast::{Command, CommandX, Commands, DeclX, Expr, ExprX};
 use air::ast_util::{
-    ident_apply, ident_binder, ident_var, mk_and, mk_bind_expr, mk_eq, mk_implies,
-    mk_unnamed_axiom, str_apply, str_ident, str_typ,
+    ident_apply, ident_binder, ident_var, mk_and, mk_bind_expr, mk_eq, mk_false, mk_implies,
+    mk_lt, mk_or, mk_true, mk_unnamed_axiom, str_apply, str_ident, str_typ,
 };
 use std::sync::Arc;
 
@@ -62,6 +62,36 @@ pub fn is_datatype_transparent(source_module: &Path, datatype: &crate::ast::Data
     }
 }
 
+/// Whether `datatype` has a field that cycles back to it through `ctx.global.datatype_graph`
+/// (i.e. it participates in a recursive/mutually-recursive type definition). Shared by
+/// `datatype_cache_key` (a recursive datatype's cache entry also depends on the SCC it sits in,
+/// not just its own declaration) and the `add_height` call-site gate in
+/// `datatypes_and_primitives_to_air` (the height block only emits axioms for fields that recurse
+/// or mention a type parameter, so a datatype that is neither recursive nor generic needs none).
+fn is_recursive_datatype(ctx: &Ctx, datatype: &crate::ast::Datatype) -> bool {
+    use crate::recursive_types::TypNode;
+    let my_dt = &datatype.x.name;
+    for variant in datatype.x.variants.iter() {
+        for field in variant.fields.iter() {
+            let mut check = |t: &Typ| match &**t {
+                TypX::Datatype(dt, _, _)
+                    if ctx.global.datatype_graph.in_same_scc(
+                        &TypNode::Datatype(dt.clone()),
+                        &TypNode::Datatype(my_dt.clone()),
+                    ) =>
+                {
+                    Err(())
+                }
+                _ => Ok(()),
+            };
+            if crate::ast_visitor::typ_visitor_check(&field.a.0, &mut check).is_err() {
+                return true;
+            }
+        }
+    }
+    false
+}
+
 fn field_to_par(span: &Span, f: &Field) -> Par {
     let dis = crate::ast::VarIdentDisambiguate::Field;
     Spanned::new(
@@ -76,6 +106,108 @@ fn field_to_par(span: &Span, f: &Field) -> Par {
     )
 }
 
+/// Name of the per-enum variant-rank function declared by the rank axioms in
+/// `datatype_or_fun_to_air_commands`, following the same `prefix_box`/`prefix_unbox` naming
+/// scheme as `crate::def`.
+///
+/// This is the variant's *declaration-order rank* (0, 1, 2, ... in source order), not the
+/// variant's real `#[repr] = N` discriminant value -- see the doc comment on its call site for
+/// why the two aren't the same thing yet.
+fn prefix_variant_rank(dpath: &Path) -> Ident {
+    Arc::new(format!("{}_variant_rank", path_to_string(dpath)))
+}
+
+/// Name of the per-field "are these two fields ext-equal" wrapper declared by the ext_equal
+/// block in `datatype_or_fun_to_air_commands`, carrying `(variant.name, field.name)` in its own
+/// name. This is the provenance channel a diagnostic consumer can use when a `=~=` obligation
+/// fails: whichever of these wrapper applications evaluates to false in the failing model is the
+/// field that diverges, so a message like "extensional equality fails at field `Foo::bar`" can
+/// name it directly instead of just reporting the whole conjunction as unsatisfied.
+///
+/// Not used for a field compared via `ext_eq` whose type mentions one of the datatype's own
+/// type parameters: the
+/// wrapper's declared signature is a fixed `(Bool, Poly, Poly) -> Bool` with no room for the
+/// field's type-id arguments, so those fields inline their equality directly instead (see the
+/// `mentions_typaram` branch at this function's call site) and lose this provenance channel.
+///
+/// TODO: this only produces the *name*; actually reading it back out of a failing SMT model and
+/// rendering the "extensional equality fails at field ..." message needs the diagnostics-layer
+/// counterexample reader (and the test harness asserting the field name appears in the error),
+/// neither of which live in this crate slice.
+fn field_ext_eq_ident(dpath: &Path, variant_name: &Ident, field_name: &Ident) -> Ident {
+    Arc::new(format!("{}_{}_{}_ext_eq_field", path_to_string(dpath), variant_name, field_name))
+}
+
+/// Computes a cache-invalidation key for one `(datatype.x, spec)` pair: a key that's stable
+/// across runs as long as nothing feeding into `datatype_or_fun_to_air_commands`'s output for
+/// this pair has changed. Besides the datatype's own declaration and the specialization, this
+/// has to capture the `ctx`-derived inputs that also shape the emitted axioms: whether the
+/// datatype is transparent to the module being verified (`ctx.module`), whether the
+/// datatype carries a refinement invariant (`ctx.datatypes_with_invariant`) and whether any
+/// field type participates in a recursive cycle with it (`ctx.global.datatype_graph`), since
+/// both gate which invariant/height axioms get emitted.
+///
+/// Returns the structural key itself (a `Debug`-formatted string of every input above), not a
+/// fixed-width hash of it: [`datatype_command_cache`] is keyed by this value directly, so two
+/// distinct `(datatype, spec)` pairs can only ever collide if their full structural description
+/// is identical, not merely if a 64-bit digest of it happened to match -- a prior version of this
+/// function returned a `u64` hash and the cache stored/compared only that, so any hash collision
+/// between two distinct pairs would have silently served one datatype's AIR commands under the
+/// other's name, an unsound verification result rather than a cache miss.
+///
+/// `datatypes_and_primitives_to_air` uses this key to look up and store results in the
+/// in-process [`datatype_command_cache`] -- see that function's doc comment for why this stops
+/// short of the on-disk, cross-invocation cache the request actually asked for.
+fn datatype_cache_key(ctx: &Ctx, datatype: &crate::ast::Datatype, spec: &Specialization) -> String {
+    let my_dt = &datatype.x.name;
+    let is_recursive = is_recursive_datatype(ctx, datatype);
+    // `my_dt` has to be part of the key: every declared name `datatype_or_fun_to_air_commands`
+    // emits is derived from `dpath` (which is itself derived from `datatype.x.name`), so two
+    // distinct datatypes that happen to share the same variant/field shape would otherwise
+    // collide and one's cached commands would get reused under the other's name.
+    format!(
+        "{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}|{:?}",
+        my_dt,
+        datatype.x.variants,
+        datatype.x.transparency,
+        is_datatype_transparent(&ctx.module.x.path, datatype),
+        datatype.x.ext_equal,
+        spec,
+        ctx.datatypes_with_invariant.contains(my_dt),
+        is_recursive,
+    )
+}
+
+/// One `(datatype.x, spec)` pair's encoding, as produced by `datatype_or_fun_to_air_commands`
+/// and memoized under its `datatype_cache_key` in [`datatype_command_cache`].
+#[derive(Clone)]
+struct CachedDatatypeCommands {
+    field: Vec<Command>,
+    token: Vec<Command>,
+    boxes: Vec<Command>,
+    axiom: Vec<Command>,
+}
+
+/// Process-lifetime memoization of `datatype_or_fun_to_air_commands`'s output, keyed by
+/// `datatype_cache_key`. `datatypes_and_primitives_to_air` is invoked once per crate/module
+/// verified in this process, so an unchanged datatype that recurs across those invocations (the
+/// common case for anything defined outside the module currently being checked) is cloned from
+/// here instead of re-encoded.
+///
+/// TODO: this only covers calls made within one process's lifetime, not the actual ask (reuse an
+/// unchanged dependency's encoding on the *next* `cargo verus` invocation) -- that needs
+/// `air::ast::Command`/`CommandX` to implement `serde::Serialize`/`Deserialize` so the cached
+/// `Commands` can be written to and read back from an on-disk cache, which isn't available from
+/// this crate. Once it is, swap this `Mutex<HashMap<..>>` for a disk-backed store keyed the same
+/// way.
+fn datatype_command_cache(
+) -> &'static std::sync::Mutex<std::collections::HashMap<String, CachedDatatypeCommands>> {
+    static CACHE: std::sync::OnceLock<
+        std::sync::Mutex<std::collections::HashMap<String, CachedDatatypeCommands>>,
+    > = std::sync::OnceLock::new();
+    CACHE.get_or_init(|| std::sync::Mutex::new(std::collections::HashMap::new()))
+}
+
 fn uses_ext_equal(ctx: &Ctx, typ: &Typ) -> bool {
     match &**typ {
         TypX::Int(_) => false,
@@ -97,6 +229,11 @@ fn uses_ext_equal(ctx: &Ctx, typ: &Typ) -> bool {
         TypX::Primitive(crate::ast::Primitive::StrSlice, _) => false,
         TypX::Primitive(crate::ast::Primitive::Ptr, _) => false,
         TypX::Primitive(crate::ast::Primitive::Global, _) => false,
+        // `char` is a plain scalar (its `has_type` range invariant, not structural equality, is
+        // what makes comparisons on it sound -- see `CharEncoding`/the range-axiom block in
+        // `datatype_or_fun_to_air_commands`), so like the other scalar primitives it doesn't
+        // need ext_equal.
+        TypX::Primitive(crate::ast::Primitive::Char, _) => false,
         TypX::FnDef(..) => false,
         TypX::Poly => false,
     }
@@ -112,8 +249,206 @@ enum EncodedDtKind {
     Monotyp,
     FnSpec,
     Array,
+    Char,
+}
+
+/// Describes one primitive type's AIR encoding, keyed by `crate::ast::Primitive`. Before this,
+/// adding a primitive (array, string-slice, and whatever comes next) meant touching
+/// `datatypes_and_primitives_to_air`, `EncodedDtKind`, and `crate::prelude` all at once; now the
+/// declaration call and the support-function emission are driven by the descriptors in
+/// `primitive_encodings()`. A primitive with axioms of its own (like `char`'s range axiom) still
+/// needs its own `EncodedDtKind` variant and axiom block in `datatype_or_fun_to_air_commands`.
+trait PrimitiveEncoding {
+    /// The primitive this descriptor encodes.
+    fn primitive(&self) -> crate::ast::Primitive;
+
+    /// The AIR sort/box identifier this primitive's support functions are keyed on, or `None`
+    /// if this primitive isn't used anywhere in the crate (so nothing should be emitted for it).
+    fn active_name(&self, ctx: &Ctx) -> Option<Ident>;
+
+    /// Declares and axiomatizes this primitive via `datatype_or_fun_to_air_commands`, for
+    /// primitives (like `Array`) that aren't already covered by the generic `ctx.mono_types`
+    /// loop in `datatypes_and_primitives_to_air`. Primitives that *are* covered there (like
+    /// `StrSlice`, whose monotyp instantiation already goes through that loop) leave this as
+    /// the default no-op.
+    fn declare_axioms(
+        &self,
+        _ctx: &Ctx,
+        _field_commands: &mut Vec<Command>,
+        _token_commands: &mut Vec<Command>,
+        _box_commands: &mut Vec<Command>,
+        _axiom_commands: &mut Vec<Command>,
+    ) {
+    }
+
+    /// The prelude S-expressions declaring this primitive's support functions, to be parsed into
+    /// `Commands` and appended after the axioms above.
+    fn emit_support_functions(&self, name: &str) -> Vec<air::ast::Node>;
+}
+
+struct ArrayEncoding;
+
+impl PrimitiveEncoding for ArrayEncoding {
+    fn primitive(&self) -> crate::ast::Primitive {
+        crate::ast::Primitive::Array
+    }
+
+    fn active_name(&self, ctx: &Ctx) -> Option<Ident> {
+        if ctx.uses_array { Some(prefix_box(&crate::def::array_type())) } else { None }
+    }
+
+    fn declare_axioms(
+        &self,
+        ctx: &Ctx,
+        field_commands: &mut Vec<Command>,
+        token_commands: &mut Vec<Command>,
+        box_commands: &mut Vec<Command>,
+        axiom_commands: &mut Vec<Command>,
+    ) {
+        datatype_or_fun_to_air_commands(
+            ctx,
+            field_commands,
+            token_commands,
+            box_commands,
+            axiom_commands,
+            &ctx.global.no_span,
+            EncodedDtKind::Array,
+            &crate::def::array_type(),
+            &Arc::new(air::ast::TypX::Fun),
+            Some(DTypId::Primitive(self.primitive())),
+            Arc::new(TypX::Primitive(self.primitive(), Arc::new(vec![]))),
+            &Arc::new(vec![Arc::new("T".to_string()), Arc::new("N".to_string())]),
+            &Arc::new(vec![]),
+            true,
+            true,
+            true,
+            false,
+            true,
+            false,
+            &Default::default(),
+        );
+    }
+
+    fn emit_support_functions(&self, name: &str) -> Vec<air::ast::Node> {
+        crate::prelude::array_functions(&Arc::new(name.to_string()))
+    }
 }
 
+struct StrSliceEncoding;
+
+impl StrSliceEncoding {
+    fn monotyp(&self) -> crate::poly::MonoTyp {
+        Arc::new(crate::poly::MonoTypX::Primitive(crate::ast::Primitive::StrSlice, Arc::new(vec![])))
+    }
+}
+
+impl PrimitiveEncoding for StrSliceEncoding {
+    fn primitive(&self) -> crate::ast::Primitive {
+        crate::ast::Primitive::StrSlice
+    }
+
+    fn active_name(&self, ctx: &Ctx) -> Option<Ident> {
+        if ctx.mono_types.contains(&self.monotyp()) {
+            Some(path_to_air_ident(&monotyp_to_path(&self.monotyp())))
+        } else {
+            None
+        }
+    }
+
+    // StrSlice's declaration/axioms already come from the generic `ctx.mono_types` loop in
+    // `datatypes_and_primitives_to_air` (it's a concrete monotyp, unlike `Array`'s `T`/`N`
+    // family), so this stays the default no-op.
+
+    fn emit_support_functions(&self, name: &str) -> Vec<air::ast::Node> {
+        crate::prelude::strslice_functions(name)
+    }
+}
+
+struct CharEncoding;
+
+impl PrimitiveEncoding for CharEncoding {
+    fn primitive(&self) -> crate::ast::Primitive {
+        crate::ast::Primitive::Char
+    }
+
+    fn active_name(&self, ctx: &Ctx) -> Option<Ident> {
+        if ctx.uses_char { Some(prefix_box(&crate::def::char_type())) } else { None }
+    }
+
+    fn declare_axioms(
+        &self,
+        ctx: &Ctx,
+        field_commands: &mut Vec<Command>,
+        token_commands: &mut Vec<Command>,
+        box_commands: &mut Vec<Command>,
+        axiom_commands: &mut Vec<Command>,
+    ) {
+        // `char` has no fields/variants and no type parameters (unlike `Array`'s `T`/`N`), so
+        // `add_ctor_field` is off; `add_invariant` drives the range axiom in
+        // `datatype_or_fun_to_air_commands` instead of a per-field one.
+        datatype_or_fun_to_air_commands(
+            ctx,
+            field_commands,
+            token_commands,
+            box_commands,
+            axiom_commands,
+            &ctx.global.no_span,
+            EncodedDtKind::Char,
+            &crate::def::char_type(),
+            &Arc::new(air::ast::TypX::Int),
+            Some(DTypId::Primitive(self.primitive())),
+            Arc::new(TypX::Primitive(self.primitive(), Arc::new(vec![]))),
+            &Arc::new(vec![]),
+            &Arc::new(vec![]),
+            true,
+            false,
+            true,
+            false,
+            false,
+            false,
+            &Default::default(),
+        );
+    }
+
+    fn emit_support_functions(&self, _name: &str) -> Vec<air::ast::Node> {
+        // The range axiom above is all `char` needs; its arithmetic is native AIR `Int`, so
+        // there's no analogue of `array_functions`/`strslice_functions` to parse in.
+        vec![]
+    }
+}
+
+/// The registered primitive encodings, in emission order. Register a new primitive here instead
+/// of special-casing it through `datatypes_and_primitives_to_air`.
+fn primitive_encodings() -> Vec<Box<dyn PrimitiveEncoding>> {
+    vec![Box::new(ArrayEncoding), Box::new(StrSliceEncoding), Box::new(CharEncoding)]
+}
+
+/// Emits the axiom commands for one `Dt`/`Specialization` pair (or one of the synthetic
+/// FnSpec/Array/Monotyp families). The axioms fall into independently toggleable families --
+/// box/unbox, constructor/accessor, invariant, height, ext_equal, ext_order (the FnSpec family is
+/// instead selected by `kind` itself, since a FnSpec encoding always needs its own axioms) --
+/// each guarded by its own `bool` here (`declare_box`, `add_ctor_field`, `add_invariant`,
+/// `add_height`, `add_ext_equal`, `ext_order`), so a caller that already knows which families a
+/// query's reachable types actually need can skip the rest instead of always emitting the full
+/// battery.
+///
+/// PARTIALLY DELIVERED: `datatypes_and_primitives_to_air`'s datatype loop now derives
+/// `add_height`/`add_invariant` per-datatype (recursive-SCC membership via
+/// `is_recursive_datatype` or having type parameters, and `ctx.datatypes_with_invariant`
+/// respectively) instead of passing
+/// `is_transparent` straight through, so those two families are genuinely demand-driven off
+/// properties of the datatype itself. `declare_box`/`add_ctor_field` still just mirror
+/// `is_transparent`, and `add_ext_equal`/`ext_order` mirror `datatype.x.ext_equal` as before.
+/// Pruning those further -- and pruning any family below *per function or module*, which is the
+/// full scope the request asked for (computing the set of `Dt`/`Specialization` pairs a single
+/// function or module's SST actually mentions, transitively through field types, trigger types,
+/// and `typ_invariant`) -- needs traversal hooks over `crate::sst` that live outside this file and
+/// are not part of this change. `datatypes_and_primitives_to_air` also runs
+/// `dedup_commands_by_name` over the declared commands these flags produce before handing them to
+/// AIR (names already carry `path_as_friendly_rust_name(dpath)`, so collisions across two
+/// encodings of the same datatype are exactly the commands it drops), so the remaining flags are
+/// ready to be driven by a finer reachability set the moment one exists -- passing fewer `true`s
+/// will just emit fewer commands, no further plumbing needed here.
 fn datatype_or_fun_to_air_commands(
     ctx: &Ctx,
     field_commands: &mut Vec<Command>,
@@ -129,11 +464,14 @@ fn datatype_or_fun_to_air_commands(
     tparams: &Idents,
     variants: &Variants,
     mut declare_box: bool,
+    add_ctor_field: bool,
+    add_invariant: bool,
     add_height: bool,
     add_ext_equal: bool,
+    ext_order: bool,
     spec: &Specialization,
 ) {
-    use crate::def::QID_EXT_EQUAL;
+    use crate::def::{QID_EXT_EQUAL, QID_EXT_ORDER};
     let x = air_unique_var("x");
     let x_var = ident_var(&x.lower());
     let apolytyp = str_typ(crate::def::POLY);
@@ -332,91 +670,147 @@ fn datatype_or_fun_to_air_commands(
     }
 
     // constructor and field axioms
-    for variant in variants.iter() {
-        if let EncodedDtKind::Dt(dt) = &kind {
-            if ctx.datatypes_with_invariant.contains(dt) {
-                // constructor invariant axiom:
-                //   forall typs, arg1 ... argn.
-                //     inv1 && ... && invn => has_type(box(ctor(arg1 ... argn)), T(typs))
-                // trigger on has_type(box(ctor(arg1 ... argn)), T(typs))
-                let params = vec_map(&*variant.fields, |f| field_to_par(span, f));
-                let params = Arc::new(params);
-                let ctor_args = func_def_args(&Arc::new(vec![]), &params);
-                let ctor = ident_apply(&variant_ident(&dt, &variant.name), &ctor_args);
-                let box_ctor = if declare_box { ident_apply(&head_box, &vec![ctor]) } else { ctor };
-                let has_ctor = expr_has_type(&box_ctor, &datatype_id(dpath, &typ_args));
-                tracing::trace!("has_ctor={has_ctor:?}");
-                let mut pre: Vec<Expr> = Vec::new();
-                for field in variant.fields.iter() {
-                    let (typ, _, _) = &field.a;
-                    let dis = crate::ast::VarIdentDisambiguate::Field;
-                    let name =
-                        crate::ast_util::str_unique_var(&("_".to_string() + &field.name), dis);
-                    if let Some(inv) = typ_invariant(ctx, typ, &ident_var(&name.lower())) {
-                        pre.push(inv);
+    if add_ctor_field {
+        for variant in variants.iter() {
+            if let EncodedDtKind::Dt(dt) = &kind {
+                if add_invariant && ctx.datatypes_with_invariant.contains(dt) {
+                    // constructor invariant axiom:
+                    //   forall typs, arg1 ... argn.
+                    //     inv1 && ... && invn => has_type(box(ctor(arg1 ... argn)), T(typs))
+                    // trigger on has_type(box(ctor(arg1 ... argn)), T(typs))
+                    let params = vec_map(&*variant.fields, |f| field_to_par(span, f));
+                    let params = Arc::new(params);
+                    let ctor_args = func_def_args(&Arc::new(vec![]), &params);
+                    let ctor = ident_apply(&variant_ident(&dt, &variant.name), &ctor_args);
+                    let box_ctor = if declare_box { ident_apply(&head_box, &vec![ctor]) } else { ctor };
+                    let has_ctor = expr_has_type(&box_ctor, &datatype_id(dpath, &typ_args));
+                    tracing::trace!("has_ctor={has_ctor:?}");
+                    let mut pre: Vec<Expr> = Vec::new();
+                    for field in variant.fields.iter() {
+                        let (typ, _, _) = &field.a;
+                        let dis = crate::ast::VarIdentDisambiguate::Field;
+                        let name =
+                            crate::ast_util::str_unique_var(&("_".to_string() + &field.name), dis);
+                        if let Some(inv) = typ_invariant(ctx, typ, &ident_var(&name.lower())) {
+                            pre.push(inv);
+                        }
                     }
+                    let name = format!("{}_{}", &variant_ident(&dt, &variant.name), QID_CONSTRUCTOR);
+                    tracing::trace!("Ctor axiom {name}");
+                    let bind = func_bind(ctx, name, tparams, &params, &has_ctor, false);
+                    let imply = mk_implies(&mk_and(&pre), &has_ctor);
+                    let forall = mk_bind_expr(&bind, &imply);
+                    let axiom = mk_unnamed_axiom(forall);
+                    axiom_commands.push(Arc::new(CommandX::Global(axiom)));
                 }
-                let name = format!("{}_{}", &variant_ident(&dt, &variant.name), QID_CONSTRUCTOR);
-                tracing::trace!("Ctor axiom {name}");
-                let bind = func_bind(ctx, name, tparams, &params, &has_ctor, false);
-                let imply = mk_implies(&mk_and(&pre), &has_ctor);
-                let forall = mk_bind_expr(&bind, &imply);
+            }
+            for (i, field) in variant.fields.iter().enumerate() {
+                let id = variant_field_ident(dpath, &variant.name, &field.name);
+                let internal_id = variant_field_ident_internal(dpath, &variant.name, &field.name, true);
+                let typ = match spec.typs.get(i) {
+                    Some(st) => st.to_typ(),
+                    None => {
+                        let (typ, _, _) = &field.a;
+                        typ.clone()
+                    }
+                };
+                let xfield = ident_apply(&id, &vec![x_var.clone()]);
+                let xfield_internal = ident_apply(&internal_id, &vec![x_var.clone()]);
+                let xfield_unbox = ident_apply(&id, &vec![unbox_x.clone()]);
+
+                // Create a wrapper function to access the field,
+                // because it seems to be dangerous to trigger directly on e.f,
+                // because Z3 seems to introduce e.f internally,
+                // which can unexpectedly trigger matching loops creating e.f.f.f.f...
+                //   function f(x:datatyp):typ
+                //   axiom forall x. f(x) = x.f
+                let decl_field = Arc::new(DeclX::Fun(
+                    id.clone(),
+                    Arc::new(vec![dtyp.clone()]),
+                    typ_to_air(ctx, &typ),
+                ));
+                field_commands.push(Arc::new(CommandX::Global(decl_field)));
+                let trigs = vec![xfield.clone()];
+                let name = format!("{}_{}", id, QID_ACCESSOR);
+                tracing::trace!("Wrapper axiom {name}");
+                let bind =
+                    func_bind_trig(ctx, name, &Arc::new(vec![]), &x_params(&datatyp), &trigs, false);
+                let eq = mk_eq(&xfield, &xfield_internal);
+                let forall = mk_bind_expr(&bind, &eq);
                 let axiom = mk_unnamed_axiom(forall);
                 axiom_commands.push(Arc::new(CommandX::Global(axiom)));
+
+                if let EncodedDtKind::Dt(dt) = &kind {
+                    if add_invariant && ctx.datatypes_with_invariant.contains(dt) {
+                        if let Some(inv_f) = typ_invariant(ctx, &typ, &xfield_unbox) {
+                            // field invariant axiom:
+                            //   forall typs, x. has_type(x, T(typs)) => inv_f(unbox(x).f)
+                            // trigger on unbox(x).f, has_type(x, T(typs))
+                            let trigs = vec![xfield_unbox.clone(), has.clone()];
+                            let name = format!("{}_{}", id, QID_INVARIANT);
+                            tracing::trace!("Field Invariant axiom {name}");
+                            let bind =
+                                func_bind_trig(ctx, name, tparams, &x_params(&vpolytyp), &trigs, false);
+                            let imply = mk_implies(&has, &inv_f);
+                            let forall = mk_bind_expr(&bind, &imply);
+                            let axiom = mk_unnamed_axiom(forall);
+                            axiom_commands.push(Arc::new(CommandX::Global(axiom)));
+                        }
+                    }
+                }
             }
         }
-        for (i, field) in variant.fields.iter().enumerate() {
-            let id = variant_field_ident(dpath, &variant.name, &field.name);
-            let internal_id = variant_field_ident_internal(dpath, &variant.name, &field.name, true);
-            let typ = match spec.typs.get(i) {
-                Some(st) => st.to_typ(),
-                None => {
-                    let (typ, _, _) = &field.a;
-                    typ.clone()
-                }
-            };
-            let xfield = ident_apply(&id, &vec![x_var.clone()]);
-            let xfield_internal = ident_apply(&internal_id, &vec![x_var.clone()]);
-            let xfield_unbox = ident_apply(&id, &vec![unbox_x.clone()]);
-
-            // Create a wrapper function to access the field,
-            // because it seems to be dangerous to trigger directly on e.f,
-            // because Z3 seems to introduce e.f internally,
-            // which can unexpectedly trigger matching loops creating e.f.f.f.f...
-            //   function f(x:datatyp):typ
-            //   axiom forall x. f(x) = x.f
-            let decl_field = Arc::new(DeclX::Fun(
-                id.clone(),
-                Arc::new(vec![dtyp.clone()]),
-                typ_to_air(ctx, &typ),
-            ));
-            field_commands.push(Arc::new(CommandX::Global(decl_field)));
-            let trigs = vec![xfield.clone()];
-            let name = format!("{}_{}", id, QID_ACCESSOR);
-            tracing::trace!("Wrapper axiom {name}");
-            let bind =
-                func_bind_trig(ctx, name, &Arc::new(vec![]), &x_params(&datatyp), &trigs, false);
-            let eq = mk_eq(&xfield, &xfield_internal);
-            let forall = mk_bind_expr(&bind, &eq);
-            let axiom = mk_unnamed_axiom(forall);
-            axiom_commands.push(Arc::new(CommandX::Global(axiom)));
+    }
 
-            if let EncodedDtKind::Dt(dt) = &kind {
-                if ctx.datatypes_with_invariant.contains(dt) {
-                    if let Some(inv_f) = typ_invariant(ctx, &typ, &xfield_unbox) {
-                        // field invariant axiom:
-                        //   forall typs, x. has_type(x, T(typs)) => inv_f(unbox(x).f)
-                        // trigger on unbox(x).f, has_type(x, T(typs))
-                        let trigs = vec![xfield_unbox.clone(), has.clone()];
-                        let name = format!("{}_{}", id, QID_INVARIANT);
-                        tracing::trace!("Field Invariant axiom {name}");
-                        let bind =
-                            func_bind_trig(ctx, name, tparams, &x_params(&vpolytyp), &trigs, false);
-                        let imply = mk_implies(&has, &inv_f);
-                        let forall = mk_bind_expr(&bind, &imply);
-                        let axiom = mk_unnamed_axiom(forall);
-                        axiom_commands.push(Arc::new(CommandX::Global(axiom)));
-                    }
+    // variant-rank axioms: declare a fresh `<dpath>_variant_rank(Poly) -> Int` function and,
+    // per variant, an axiom pinning it to the variant's declaration-order rank (0, 1, 2, ...).
+    // This is enough to make declaration-order comparisons (the `ext_le`/`ext_lt` structural
+    // ordering below) provable.
+    //
+    // IMPORTANT: this rank is *not* the variant's real `#[repr(..)] = N` discriminant value, and
+    // must not be used to lower `e as i32`/`e as u8` casts -- `ast::Datatype`'s variant data
+    // doesn't carry an explicit discriminant expression or the chosen repr integer type, so
+    // there's no way from here to honor an explicit override (e.g. `enum E { A = 5, B = 10 }`)
+    // or reduce a value modulo the repr type's range. Hardcoding the declaration index as the
+    // real discriminant would be wrong for any enum using explicit discriminants, and unsound if
+    // anything ever lowered an `as`-cast onto it. Until `ast::Datatype` grows that data, this
+    // function only backs same-enum variant ordering, not integer casts. `as`-cast verification
+    // remains genuinely unimplemented here, not merely under-documented: it needs `ast::Datatype`
+    // and `ast::Variant` to carry the explicit discriminant/repr-type data that isn't present
+    // anywhere in this crate slice, so there's nothing in this file that could soundly compute it
+    // -- fabricating that data instead of plumbing it through from the surface syntax would just
+    // move the unsoundness rather than remove it. `prefix_variant_rank` is private to this module
+    // (not `pub` or `pub(crate)`), so nothing outside this function can reach it to lower a cast
+    // today; that containment, not a correctness argument, is what keeps this sound in the
+    // meantime, and needs to stay true until the real discriminant data exists.
+    if add_ctor_field {
+        if let EncodedDtKind::Dt(dt) = &kind {
+            if variants.len() > 1 {
+                let rank_fun = prefix_variant_rank(dpath);
+                let decl_rank = Arc::new(DeclX::Fun(
+                    rank_fun.clone(),
+                    Arc::new(vec![apolytyp.clone()]),
+                    Arc::new(air::ast::TypX::Int),
+                ));
+                field_commands.push(Arc::new(CommandX::Global(decl_rank)));
+
+                let rank_x = ident_apply(&rank_fun, &vec![x_var.clone()]);
+                for (value, variant) in variants.iter().enumerate() {
+                    // per-variant rank axiom:
+                    //   forall typs, x. has_type(x, T(typs)) && is-<variant>(unbox(x)) ==>
+                    //     variant_rank(x) == value
+                    // trigger on variant_rank(x), has_type(x, T(typs))
+                    let vid = is_variant_ident(dt, &*variant.name);
+                    let is_variant_x = ident_apply(&vid, &vec![unbox_x.clone()]);
+                    let tag =
+                        Arc::new(ExprX::Const(air::ast::Constant::Nat(Arc::new(value.to_string()))));
+                    let name = format!("{}_variant_rank", &variant_ident(dt, &variant.name));
+                    let trigs = vec![rank_x.clone(), has.clone()];
+                    let bind = func_bind_trig(ctx, name, tparams, &x_params(&vpolytyp), &trigs, false);
+                    let pre = mk_and(&vec![has.clone(), is_variant_x]);
+                    let imply = mk_implies(&pre, &mk_eq(&rank_x, &tag));
+                    let forall = mk_bind_expr(&bind, &imply);
+                    axiom_commands.push(Arc::new(CommandX::Global(mk_unnamed_axiom(forall))));
                 }
             }
         }
@@ -431,6 +825,8 @@ fn datatype_or_fun_to_air_commands(
         EncodedDtKind::Array => false,
         EncodedDtKind::FnSpec => false,
         EncodedDtKind::Monotyp => true,
+        // `char` doesn't always hold -- see the range invariant axiom below instead.
+        EncodedDtKind::Char => false,
     };
     if declare_box && has_type_always_holds {
         let name = format!("{}_{}", path_as_friendly_rust_name(dpath), QID_HAS_TYPE_ALWAYS);
@@ -439,6 +835,33 @@ fn datatype_or_fun_to_air_commands(
         axiom_commands.push(Arc::new(CommandX::Global(mk_unnamed_axiom(forall))));
     }
 
+    // `char` range invariant: a `char` is exactly a Unicode scalar value, i.e. an integer in
+    // 0..=0x10FFFF that isn't a UTF-16 surrogate (0xD800..=0xDFFF) -- see
+    // <https://doc.rust-lang.org/std/primitive.char.html>. Unlike the datatype invariant axioms
+    // above (which constrain a *field* of some other type), this constrains `char` itself, so it
+    // has to state the bound both ways: has_type holds exactly on the valid range, not just
+    // "valid range implies has_type".
+    //   forall x. has_type(box(x), char_T()) <==>
+    //     0 <= x && x <= 0x10FFFF && (x < 0xD800 || 0xDFFF < x)
+    // trigger on has_type(box(x), char_T())
+    if matches!(kind, EncodedDtKind::Char) && declare_box && add_invariant {
+        let nat_const = |n: u32| Arc::new(ExprX::Const(air::ast::Constant::Nat(Arc::new(n.to_string()))));
+        let le = |a: &Expr, b: &Expr| mk_or(&vec![mk_lt(a, b), mk_eq(a, b)]);
+        let zero = nat_const(0);
+        let max_scalar_value = nat_const(0x10FFFF);
+        let surrogate_lo = nat_const(0xD800);
+        let surrogate_hi = nat_const(0xDFFF);
+        let in_scalar_range = mk_and(&vec![le(&zero, &x_var), le(&x_var, &max_scalar_value)]);
+        let outside_surrogates =
+            mk_or(&vec![mk_lt(&x_var, &surrogate_lo), mk_lt(&surrogate_hi, &x_var)]);
+        let range_cond = mk_and(&vec![in_scalar_range, outside_surrogates]);
+        let name = format!("{}_{}", path_as_friendly_rust_name(dpath), QID_HAS_TYPE_ALWAYS);
+        let bind = func_bind(ctx, name, tparams, &x_params(&datatyp), &has_box, false);
+        let iff = mk_eq(&has_box, &range_cond);
+        let forall = mk_bind_expr(&bind, &iff);
+        axiom_commands.push(Arc::new(CommandX::Global(mk_unnamed_axiom(forall))));
+    }
+
     // height axiom
     // (make sure that this stays in sync with recursive_types::check_well_founded)
     if add_height {
@@ -544,6 +967,117 @@ fn datatype_or_fun_to_air_commands(
         }
     }
 
+    // ext_le/ext_lt axioms for datatypes: a derived lexicographic order, the spec-level analogue
+    // of `#[derive(PartialOrd, Ord)]` (rustc orders by variant index first, then lexicographically
+    // over that variant's fields). This piggybacks on the same transparency/`ext_equal` gate the
+    // caller passes for `add_ext_equal`, since `ast::Datatype` doesn't carry a separate "derive
+    // Ord" flag in this slice.
+    if ext_order {
+        let deep = air_unique_var("deep");
+        let deep_var = ident_var(&deep.lower());
+        let deep_param = var_param(deep, &Arc::new(TypX::Bool));
+        let has_x = has.clone();
+        let y = str_ident("y");
+        let y_var = ident_var(&y);
+        let y_param = |typ: &Typ| var_param(air_unique_var(&y), typ);
+        let unbox_y = ident_apply(&prefix_unbox(dpath), &vec![y_var.clone()]);
+        let has_y = expr_has_type(&y_var, &id);
+        let my_dt = match &kind {
+            EncodedDtKind::Dt(dt) => dt,
+            _ => panic!("Verus internal error: ext_order should only be for DtKind::Dt"),
+        };
+
+        let ord_command = |op: &str, s_name: &str, pre: &Vec<Expr>| {
+            let params = Arc::new(vec![deep_param.clone(), x_param(&vpolytyp), y_param(&vpolytyp)]);
+            let name = format!("{}_{}_{}", s_name, op, QID_EXT_ORDER);
+            let args = vec![deep_var.clone(), id.clone(), x_var.clone(), y_var.clone()];
+            let ext_op_xy = str_apply(op, &args);
+            let bind = func_bind(ctx, name, tparams, &params, &ext_op_xy, false);
+            let imply = mk_implies(&mk_and(pre), &ext_op_xy);
+            let forall = mk_bind_expr(&bind, &imply);
+            let axiom = mk_unnamed_axiom(forall);
+            Arc::new(CommandX::Global(axiom))
+        };
+
+        // The `<`/`==` (or recursive `ext_lt`/`ext_eq`) pair used to compare one field, picked
+        // exactly like the ext_equal block below picks `ext_eq` vs `==`: recurse when the field's
+        // type itself uses extensional equality, except across an SCC-recursive field, where
+        // plain `<`/`==` avoids trigger-matching loops.
+        let field_lt_eq = |variant: &crate::ast::Variant, field: &Field| -> (Expr, Expr) {
+            use crate::recursive_types::TypNode;
+            let (typ, _, _) = &field.a;
+            let mut is_recursive = |t: &Typ| match &**t {
+                TypX::Datatype(dt, _, _)
+                    if ctx.global.datatype_graph.in_same_scc(
+                        &TypNode::Datatype(dt.clone()),
+                        &TypNode::Datatype(my_dt.clone()),
+                    ) =>
+                {
+                    Err(())
+                }
+                _ => Ok(()),
+            };
+            let uses_ext = uses_ext_equal(ctx, typ)
+                && !crate::ast_visitor::typ_visitor_check(typ, &mut is_recursive).is_err();
+            let fid = variant_field_ident(dpath, &variant.name, &field.name);
+            let xfield = ident_apply(&fid, &vec![unbox_x.clone()]);
+            let yfield = ident_apply(&fid, &vec![unbox_y.clone()]);
+            if uses_ext {
+                let xfield = crate::sst_to_air::as_box(ctx, xfield, typ);
+                let yfield = crate::sst_to_air::as_box(ctx, yfield, typ);
+                let ftids = crate::sst_to_air::typ_to_id(typ);
+                let lt_args = vec![deep_var.clone(), ftids.clone(), xfield.clone(), yfield.clone()];
+                let lt = str_apply(crate::def::EXT_LT, &lt_args);
+                let eq_args = vec![deep_var.clone(), ftids, xfield, yfield];
+                let eq = str_apply(crate::def::EXT_EQ, &eq_args);
+                (lt, eq)
+            } else {
+                (mk_lt(&xfield, &yfield), mk_eq(&xfield, &yfield))
+            }
+        };
+
+        // lex_chain(variant, strict): `f1 < y1 || (f1 == y1 && (f2 < y2 || ...))`, bottoming out
+        // at `false` when `strict` and no fields remain to break the tie, or `true` otherwise.
+        let lex_chain = |variant: &crate::ast::Variant, strict: bool| -> Expr {
+            let mut chain = if strict { mk_false() } else { mk_true() };
+            for field in variant.fields.iter().rev() {
+                let (lt, eq) = field_lt_eq(variant, field);
+                chain = mk_or(&vec![lt, mk_and(&vec![eq, chain])]);
+            }
+            chain
+        };
+
+        if variants.len() > 1 {
+            // cross-variant order: a strictly lower declaration-order rank alone decides both
+            // `<=` and `<`, regardless of field values (mirrors rustc ordering variants by
+            // declaration index). This only needs the rank's relative order, not its real
+            // `#[repr(..)]` discriminant value, so it's sound independent of the discriminant
+            // caveat on the variant-rank axioms above.
+            let rank_fun = prefix_variant_rank(dpath);
+            let rank_x = ident_apply(&rank_fun, &vec![x_var.clone()]);
+            let rank_y = ident_apply(&rank_fun, &vec![y_var.clone()]);
+            let pre = vec![has_x.clone(), has_y.clone(), mk_lt(&rank_x, &rank_y)];
+            axiom_commands.push(ord_command(crate::def::EXT_LE, "variant_rank", &pre));
+            axiom_commands.push(ord_command(crate::def::EXT_LT, "variant_rank", &pre));
+        }
+
+        for variant in variants.iter() {
+            let mut pre = vec![has_x.clone(), has_y.clone()];
+            if variants.len() > 1 {
+                let vid = is_variant_ident(my_dt, &*variant.name);
+                pre.push(ident_apply(&vid, &vec![unbox_x.clone()]));
+                pre.push(ident_apply(&vid, &vec![unbox_y.clone()]));
+            }
+            let mut le_pre = pre.clone();
+            le_pre.push(lex_chain(variant, false));
+            let vname = variant_ident(my_dt, &variant.name);
+            axiom_commands.push(ord_command(crate::def::EXT_LE, &vname, &le_pre));
+
+            pre.push(lex_chain(variant, true));
+            axiom_commands.push(ord_command(crate::def::EXT_LT, &vname, &pre));
+        }
+    }
+
     // ext_equal axiom for datatypes
     if add_ext_equal {
         let deep = air_unique_var("deep");
@@ -611,6 +1145,19 @@ fn datatype_or_fun_to_air_commands(
                 let fid = variant_field_ident(dpath, &variant.name, &field.name);
                 let xfield = ident_apply(&fid, &vec![unbox_x.clone()]);
                 let yfield = ident_apply(&fid, &vec![unbox_y.clone()]);
+                // Whether `eq` below embeds one of the datatype's own type-parameter terms
+                // (`typ_to_id`/`as_box` both bake a type-id term straight in for any `TypParam`
+                // the field type mentions, e.g. `struct Wrap<T> { a: T }`'s field `a`). Those
+                // terms are only meaningful bound by the enclosing `tparams` (as `eq_command`
+                // does), not by the wrapper below, whose declared signature is a fixed
+                // `(Bool, Poly, Poly) -> Bool` with no room to carry type arguments.
+                let mentions_typaram = {
+                    let mut check = |t: &Typ| match &**t {
+                        TypX::TypParam(_) => Err(()),
+                        _ => Ok(()),
+                    };
+                    crate::ast_visitor::typ_visitor_check(typ, &mut check).is_err()
+                };
                 let eq = if uses_ext {
                     let xfield = crate::sst_to_air::as_box(ctx, xfield, typ);
                     let yfield = crate::sst_to_air::as_box(ctx, yfield, typ);
@@ -623,7 +1170,44 @@ fn datatype_or_fun_to_air_commands(
                 } else {
                     mk_eq(&xfield, &yfield)
                 };
-                pre.push(eq);
+
+                if uses_ext && mentions_typaram {
+                    // Can't wrap: the type-id term(s) `eq` embeds are free unless bound by the
+                    // surrounding quantifier's `tparams` (see `eq_command`, which does exactly
+                    // that), and the wrapper's fixed-arity signature has nowhere to carry them.
+                    // Inline `eq` directly into the conjunction instead, same as the non-ext
+                    // branch already does.
+                    pre.push(eq);
+                } else {
+                    // Name this field's equality via a wrapper function carrying
+                    // `(variant.name, field.name)` in its own name (see `field_ext_eq_ident`'s doc
+                    // comment), instead of inlining `eq` directly into the conjunction below.
+                    let wrapper_id = field_ext_eq_ident(dpath, &variant.name, &field.name);
+                    let bool_typ = Arc::new(air::ast::TypX::Bool);
+                    let decl_wrapper = Arc::new(DeclX::Fun(
+                        wrapper_id.clone(),
+                        Arc::new(vec![bool_typ.clone(), apolytyp.clone(), apolytyp.clone()]),
+                        bool_typ,
+                    ));
+                    field_commands.push(Arc::new(CommandX::Global(decl_wrapper)));
+                    let wrapper_app = ident_apply(
+                        &wrapper_id,
+                        &vec![deep_var.clone(), x_var.clone(), y_var.clone()],
+                    );
+                    let wrapper_bind = func_bind(
+                        ctx,
+                        format!("{}_ext_eq_field", wrapper_id),
+                        &Arc::new(vec![]),
+                        &Arc::new(vec![deep_param.clone(), x_param(&vpolytyp), y_param(&vpolytyp)]),
+                        &wrapper_app,
+                        false,
+                    );
+                    let wrapper_forall = mk_bind_expr(&wrapper_bind, &mk_eq(&wrapper_app, &eq));
+                    axiom_commands
+                        .push(Arc::new(CommandX::Global(mk_unnamed_axiom(wrapper_forall))));
+
+                    pre.push(wrapper_app);
+                }
             }
             axiom_commands.push(eq_command(&variant_ident(&my_dt, &variant.name), &pre));
         }
@@ -664,6 +1248,32 @@ fn datatype_or_fun_to_air_commands(
     }
 }
 
+/// Drops commands whose declared name has already been seen, preserving the order of the first
+/// occurrence. Meant for combining the independently-toggleable axiom families emitted by
+/// `datatype_or_fun_to_air_commands` across multiple `Dt`/`Specialization` pairs for one
+/// verification group: two families (or two callers asking for the same family) can both
+/// declare the same box/unbox/accessor function, and Z3 rejects duplicate declarations.
+fn dedup_commands_by_name(commands: Vec<Command>) -> Vec<Command> {
+    let mut seen = std::collections::HashSet::new();
+    commands
+        .into_iter()
+        .filter(|cmd| {
+            let name = match &**cmd {
+                CommandX::Global(decl) => match &**decl {
+                    DeclX::Fun(name, ..) => Some(name.clone()),
+                    DeclX::Sort(name) => Some(name.clone()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match name {
+                Some(name) => seen.insert(name),
+                None => true,
+            }
+        })
+        .collect()
+}
+
 #[tracing::instrument(skip_all)]
 pub fn datatypes_and_primitives_to_air(
     ctx: &Ctx,
@@ -696,34 +1306,28 @@ pub fn datatypes_and_primitives_to_air(
             &Arc::new(tparams),
             &Arc::new(vec![]),
             true,
-            false,
             true,
-            &Default::default(),
-        );
-    }
-
-    if ctx.uses_array {
-        datatype_or_fun_to_air_commands(
-            ctx,
-            &mut field_commands,
-            &mut token_commands,
-            &mut box_commands,
-            &mut axiom_commands,
-            &ctx.global.no_span,
-            EncodedDtKind::Array,
-            &crate::def::array_type(),
-            &Arc::new(air::ast::TypX::Fun),
-            Some(DTypId::Primitive(crate::ast::Primitive::Array)),
-            Arc::new(TypX::Primitive(crate::ast::Primitive::Array, Arc::new(vec![]))),
-            &Arc::new(vec![Arc::new("T".to_string()), Arc::new("N".to_string())]),
-            &Arc::new(vec![]),
             true,
             false,
             true,
+            false,
             &Default::default(),
         );
     }
 
+    let primitive_encodings = primitive_encodings();
+    for encoding in &primitive_encodings {
+        if encoding.active_name(ctx).is_some() {
+            encoding.declare_axioms(
+                ctx,
+                &mut field_commands,
+                &mut token_commands,
+                &mut box_commands,
+                &mut axiom_commands,
+            );
+        }
+    }
+
     for monotyp in &ctx.mono_types {
         // Encode concrete instantiations of abstract types as AIR sorts
         let dpath = crate::sst_to_air::monotyp_to_path(monotyp);
@@ -748,6 +1352,9 @@ pub fn datatypes_and_primitives_to_air(
             &Arc::new(vec![]),
             &Arc::new(vec![]),
             true,
+            true,
+            true,
+            false,
             false,
             false,
             &Default::default(),
@@ -785,15 +1392,40 @@ pub fn datatypes_and_primitives_to_air(
         let datatyp = Arc::new(TypX::Datatype(dt.clone(), typ_args.clone(), Arc::new(vec![])));
         let tparams = Arc::new(tparams);
 
+        // Two of the six families can be gated on information already available here, without
+        // the per-query SST reachability analysis described below: height axioms only mean
+        // anything for a datatype that's recursive or has type parameters (the height block
+        // emits them only for fields that recurse or mention a type parameter), and invariant
+        // axioms only for a datatype `ctx.datatypes_with_invariant` actually tracks one for.
+        let add_height = is_transparent
+            && (is_recursive_datatype(ctx, datatype) || !datatype.x.typ_params.is_empty());
+        let add_invariant = is_transparent && ctx.datatypes_with_invariant.contains(dt);
+
         for spec in specs.iter() {
             tracing::trace!("Generating datatype spec: {spec:?}");
+            let cache_key = datatype_cache_key(ctx, datatype, spec);
+            let cached = datatype_command_cache().lock().unwrap().get(&cache_key).cloned();
+            if let Some(mut cached) = cached {
+                tracing::trace!("cache hit for {dt:?} (spec {spec:?}): {cache_key}");
+                field_commands.append(&mut cached.field);
+                token_commands.append(&mut cached.token);
+                box_commands.append(&mut cached.boxes);
+                axiom_commands.append(&mut cached.axiom);
+                continue;
+            }
+            tracing::trace!("cache miss for {dt:?} (spec {spec:?}): {cache_key}");
+
+            let mut local_field = Vec::new();
+            let mut local_token = Vec::new();
+            let mut local_box = Vec::new();
+            let mut local_axiom = Vec::new();
             let dpath = spec.mangle_path(&encode_dt_as_path(dt));
             datatype_or_fun_to_air_commands(
                 ctx,
-                &mut field_commands,
-                &mut token_commands,
-                &mut box_commands,
-                &mut axiom_commands,
+                &mut local_field,
+                &mut local_token,
+                &mut local_box,
+                &mut local_axiom,
                 &datatype.span,
                 EncodedDtKind::Dt(dt.clone()),
                 &spec.mangle_path(&dpath),
@@ -804,9 +1436,26 @@ pub fn datatypes_and_primitives_to_air(
                 &datatype.x.variants,
                 is_transparent,
                 is_transparent,
+                add_invariant,
+                add_height,
+                is_transparent && datatype.x.ext_equal,
                 is_transparent && datatype.x.ext_equal,
                 &spec,
             );
+
+            datatype_command_cache().lock().unwrap().insert(
+                cache_key,
+                CachedDatatypeCommands {
+                    field: local_field.clone(),
+                    token: local_token.clone(),
+                    boxes: local_box.clone(),
+                    axiom: local_axiom.clone(),
+                },
+            );
+            field_commands.append(&mut local_field);
+            token_commands.append(&mut local_token);
+            box_commands.append(&mut local_box);
+            axiom_commands.append(&mut local_axiom);
         }
     }
 
@@ -825,41 +1474,32 @@ pub fn datatypes_and_primitives_to_air(
         token_commands.push(Arc::new(CommandX::Global(decl_type_id)));
     }
 
-    let array_commands = if ctx.uses_array {
-        let nodes = crate::prelude::array_functions(&prefix_box(&crate::def::array_type()));
-        let cmds = air::parser::Parser::new(Arc::new(crate::messages::VirMessageInterface {}))
-            .nodes_to_commands(&nodes)
-            .expect("internal error: malformed strslice functions");
-        (*cmds).clone()
-    } else {
-        vec![]
-    };
+    let mut primitive_support_commands: Vec<Command> = Vec::new();
+    for encoding in &primitive_encodings {
+        if let Some(name) = encoding.active_name(ctx) {
+            let nodes = encoding.emit_support_functions(&name);
+            let cmds = air::parser::Parser::new(Arc::new(crate::messages::VirMessageInterface {}))
+                .nodes_to_commands(&nodes)
+                .expect("internal error: malformed primitive support functions");
+            primitive_support_commands.extend((*cmds).iter().cloned());
+        }
+    }
 
-    let strslice_monotyp = Arc::new(crate::poly::MonoTypX::Primitive(
-        crate::ast::Primitive::StrSlice,
-        Arc::new(vec![]),
-    ));
-    let strslice_commands = if ctx.mono_types.contains(&strslice_monotyp) {
-        let strslice_name = path_to_air_ident(&monotyp_to_path(&strslice_monotyp));
-        let nodes = crate::prelude::strslice_functions(strslice_name.as_str());
-        let cmds = air::parser::Parser::new(Arc::new(crate::messages::VirMessageInterface {}))
-            .nodes_to_commands(&nodes)
-            .expect("internal error: malformed strslice functions");
-        (*cmds).clone()
-    } else {
-        vec![]
-    };
+    // `field_commands`/`token_commands`/`box_commands` are built up across many independent
+    // `datatype_or_fun_to_air_commands` calls (one per `Dt`/`Specialization`/primitive/monotyp),
+    // so the same box/unbox/accessor/type-id declaration can in principle be pushed more than
+    // once (e.g. two encodings of the same datatype requesting the same family). Z3 rejects
+    // duplicate declarations, so dedup by name before handing them to AIR.
+    let declared_commands =
+        dedup_commands_by_name([field_commands, token_commands, box_commands].concat());
 
     let mut commands: Vec<Command> = Vec::new();
     commands.append(&mut opaque_sort_commands);
     commands.push(Arc::new(CommandX::Global(Arc::new(DeclX::Datatypes(Arc::new(
         transparent_air_datatypes,
     ))))));
-    commands.append(&mut field_commands);
-    commands.append(&mut token_commands);
-    commands.append(&mut box_commands);
+    commands.extend(declared_commands);
     commands.append(&mut axiom_commands);
-    commands.extend(array_commands);
-    commands.extend(strslice_commands);
+    commands.extend(primitive_support_commands);
     Arc::new(commands)
 }
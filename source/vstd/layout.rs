@@ -159,9 +159,11 @@ pub broadcast proof fn layout_of_primitives()
 {
 }
 
-// TODO: Are these the right triggers?
-// The alignment is at least 1 by https://doc.rust-lang.org/reference/type-layout.html#r-layout.properties.size
-// TODO: specify that the alignment is always a power of 2?
+// The alignment is at least 1 by https://doc.rust-lang.org/reference/type-layout.html#r-layout.properties.size,
+// and is always a power of 2 (https://doc.rust-lang.org/reference/type-layout.html#r-layout.properties.align).
+// rustc also rejects any alignment larger than 2^29
+// (https://doc.rust-lang.org/reference/type-layout.html#r-layout.properties.align.max), so
+// `size_of`/`align_of` always describe a `valid_layout`.
 #[verifier::external_body]
 pub broadcast proof fn align_properties<T>()
     ensures
@@ -169,6 +171,9 @@ pub broadcast proof fn align_properties<T>()
         #![trigger align_of::<T>()]
         size_of::<T>() % align_of::<T>() == 0,
         align_of::<T>() > 0,
+        is_power_2(align_of::<T>() as int),
+        align_of::<T>() <= 0x2000_0000,
+        valid_layout(size_of::<T>() as usize, align_of::<T>() as usize),
 ;
 
 pub proof fn usize_size_pow2()
@@ -191,6 +196,41 @@ pub broadcast proof fn layout_of_unit_tuple()
         align_of::<()>() == 1,
 ;
 
+/// Size and alignment of a fixed-size array ([Reference](https://doc.rust-lang.org/reference/type-layout.html#r-layout.array)).
+#[verifier::external_body]
+pub broadcast proof fn layout_of_array<T, const N: usize>()
+    requires
+        is_sized::<T>(),
+    ensures
+        #![trigger size_of::<[T; N]>()]
+        #![trigger align_of::<[T; N]>()]
+        size_of::<[T; N]>() == size_of::<T>() * N,
+        align_of::<[T; N]>() == align_of::<T>(),
+        is_sized::<[T; N]>(),
+;
+
+/// Size and alignment of a 2-tuple ([Reference](https://doc.rust-lang.org/reference/type-layout.html#r-layout.tuple)).
+/// As with other multi-field layouts, Rust doesn't guarantee the fields are laid out in
+/// declaration order, but `Layout::extend` is still a sound (if conservative) upper bound.
+/// Unlike `Layout::extend` alone, the overall type's size must be a multiple of its own
+/// alignment, so the extended layout is additionally `pad_to_align`ed.
+#[verifier::external_body]
+pub broadcast proof fn layout_of_pair<A, B>()
+    requires
+        is_sized::<A>(),
+        is_sized::<B>(),
+    ensures
+        #![trigger size_of::<(A, B)>()]
+        #![trigger align_of::<(A, B)>()]
+        size_of::<(A, B)>() == Layout { size: size_of::<A>(), align: align_of::<A>() }.extend(
+            Layout { size: size_of::<B>(), align: align_of::<B>() },
+        ).0.pad_to_align().size,
+        align_of::<(A, B)>() == Layout { size: size_of::<A>(), align: align_of::<A>() }.extend(
+            Layout { size: size_of::<B>(), align: align_of::<B>() },
+        ).0.pad_to_align().align,
+        is_sized::<(A, B)>(),
+;
+
 /// Pointers and references have the same layout. Mutability of the pointer or reference does not change the layout. ([Reference](https://doc.rust-lang.org/reference/type-layout.html#r-layout.pointer.intro).)
 #[verifier::external_body]
 pub broadcast proof fn layout_of_references_and_pointers<T: ?Sized>()
@@ -221,9 +261,323 @@ pub broadcast proof fn layout_of_references_and_pointers_for_sized_types<T: Size
 pub broadcast group group_layout_axioms {
     layout_of_primitives,
     layout_of_unit_tuple,
+    layout_of_array,
+    layout_of_pair,
     layout_of_references_and_pointers,
     layout_of_references_and_pointers_for_sized_types,
     align_properties,
 }
 
+//////////////////////////////////////
+// A spec-level mirror of `core::alloc::Layout`, with the composition arithmetic
+// (`extend`/`repeat`/`array`) that type provides.
+pub ghost struct Layout {
+    pub size: nat,
+    pub align: nat,
+}
+
+impl Layout {
+    pub open spec fn valid(self) -> bool {
+        valid_layout(self.size as usize, self.align as usize)
+    }
+
+    /// Matches `core::alloc::Layout::padding_needed_for`: the amount that has to be added
+    /// to `self.size` to round it up to a multiple of `align`.
+    pub open spec fn padding_needed_for(self, align: nat) -> nat {
+        let rem = self.size % align;
+        if rem == 0 {
+            0
+        } else {
+            (align - rem) as nat
+        }
+    }
+
+    /// Matches `core::alloc::Layout::extend`: the layout of `self` followed by `next`
+    /// (with `next` placed at the returned offset so it satisfies its own alignment), and
+    /// the offset at which `next` starts.
+    pub open spec fn extend(self, next: Layout) -> (Layout, nat) {
+        let offset = (self.size + self.padding_needed_for(next.align)) as nat;
+        let size = (offset + next.size) as nat;
+        let align = if self.align >= next.align {
+            self.align
+        } else {
+            next.align
+        };
+        (Layout { size, align }, offset)
+    }
+
+    /// Matches `core::alloc::Layout::repeat`: the layout of `n` repetitions of `self`,
+    /// each aligned to `self.align`, and the stride between repetitions.
+    pub open spec fn repeat(self, n: nat) -> (Layout, nat) {
+        let stride = (self.size + self.padding_needed_for(self.align)) as nat;
+        (Layout { size: (stride * n) as nat, align: self.align }, stride)
+    }
+
+    /// Matches `core::alloc::Layout::pad_to_align`: round `self.size` up to a multiple of
+    /// `self.align`, leaving `self.align` unchanged.
+    pub open spec fn pad_to_align(self) -> Layout {
+        Layout { size: (self.size + self.padding_needed_for(self.align)) as nat, align: self.align }
+    }
+
+    /// Matches `core::alloc::Layout::array::<T>(n)` given the layout of `T`.
+    ///
+    /// Takes `elem` as an already-built [`Layout`] rather than being generic over `T` directly,
+    /// matching the style of [`Self::extend`]/[`Self::repeat`] (which likewise compose on
+    /// [`Layout`] values, not by reaching into `size_of`/`align_of` themselves) -- this lets
+    /// `array` compose with a layout built any other way (e.g. `extend`ed from several fields),
+    /// not just one read straight off a type. See [`Self::array_for`] for the `T`-generic form.
+    pub open spec fn array(elem: Layout, n: nat) -> Layout {
+        elem.repeat(n).0
+    }
+
+    /// `T`-generic convenience wrapper around [`Self::array`], for the common case where the
+    /// element layout is just "the layout of `T`" rather than something composed by hand.
+    pub open spec fn array_for<T>(n: nat) -> Layout {
+        Self::array(Layout { size: size_of::<T>(), align: align_of::<T>() }, n)
+    }
+}
+
+/// `extend`ing two valid layouts produces a valid layout, as long as the combined size
+/// still fits the `isize::MAX` bound `valid_layout` imposes (mirrors `Layout::extend`
+/// returning `Err` instead of overflowing).
+#[verifier::external_body]
+pub proof fn lemma_layout_extend_valid(layout: Layout, next: Layout)
+    requires
+        layout.valid(),
+        next.valid(),
+        layout.extend(next).0.size <= isize::MAX as int - (isize::MAX as int
+            % layout.extend(next).0.align as int),
+    ensures
+        layout.extend(next).0.valid(),
+        layout.extend(next).1 + next.size <= layout.extend(next).0.size,
+;
+
+/// `repeat`ing a valid layout produces a valid layout, as long as the combined size still
+/// fits the `isize::MAX` bound `valid_layout` imposes.
+#[verifier::external_body]
+pub proof fn lemma_layout_repeat_valid(layout: Layout, n: nat)
+    requires
+        layout.valid(),
+        layout.repeat(n).0.size <= isize::MAX as nat,
+    ensures
+        layout.repeat(n).0.valid(),
+;
+
+/// `Layout::array` computed from a type's own size/align matches the `[T; N]` facts in
+/// `layout_of_array` -- since `align_properties` guarantees `size_of::<T>()` is already a
+/// multiple of `align_of::<T>()`, no padding needs to be inserted between elements.
+pub proof fn lemma_layout_array_matches_fixed_array<T>(n: nat)
+    ensures
+        Layout::array(Layout { size: size_of::<T>(), align: align_of::<T>() }, n).size
+            == size_of::<T>() * n,
+{
+    broadcast use group_layout_axioms;
+}
+
+impl View for core::alloc::Layout {
+    type V = Layout;
+
+    uninterp spec fn view(&self) -> Self::V;
+}
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification[ core::alloc::Layout::size ](layout: &core::alloc::Layout) -> (size: usize)
+    ensures
+        size as nat == layout@.size,
+    opens_invariants none
+    no_unwind
+;
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification[ core::alloc::Layout::align ](layout: &core::alloc::Layout) -> (align: usize)
+    ensures
+        align as nat == layout@.align,
+    opens_invariants none
+    no_unwind
+;
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification[ core::alloc::Layout::from_size_align ](
+    size: usize,
+    align: usize,
+) -> (res: Result<core::alloc::Layout, core::alloc::LayoutError>)
+    ensures
+        valid_layout(size, align) ==> res is Ok && res->Ok_0@ == (Layout {
+            size: size as nat,
+            align: align as nat,
+        }),
+        !valid_layout(size, align) ==> res is Err,
+    opens_invariants none
+    no_unwind
+;
+
+/// Convenience wrapper around the `core::alloc::Layout::from_size_align` assume_specification
+/// above, for callers that only care whether construction succeeded and not the `LayoutError`
+/// (which carries no queryable information anyway). Returns the real, executable
+/// `core::alloc::Layout` -- not the ghost [`Layout`] type -- since that's what callers need to
+/// actually hand to a `GlobalAlloc`.
+#[verifier::external_body]
+pub fn from_size_align(size: usize, align: usize) -> (result: Option<core::alloc::Layout>)
+    ensures
+        valid_layout(size, align) ==> result is Some && result->Some_0@ == (Layout {
+            size: size as nat,
+            align: align as nat,
+        }),
+        !valid_layout(size, align) ==> result is None,
+    opens_invariants none
+    no_unwind
+{
+    core::alloc::Layout::from_size_align(size, align).ok()
+}
+
+//////////////////////////////////////
+// Zero-copy byte reinterpretation
+// Marker predicates mirroring the zerocopy crate's `FromBytes`/`IntoBytes`/`Unaligned`
+// traits, plus the `bytes_of`/`from_bytes` spec functions they make sound to write.
+
+/// Every bit pattern of the right size is a valid value of `V` (mirrors `zerocopy::FromBytes`).
+pub uninterp spec fn is_from_bytes<V>() -> bool;
+
+/// Every value of `V` has no padding or uninitialized bytes, so its byte representation is
+/// fully meaningful (mirrors `zerocopy::IntoBytes`).
+pub uninterp spec fn is_as_bytes<V>() -> bool;
+
+/// `V`'s alignment requirement is 1 (mirrors `zerocopy::Unaligned`).
+pub uninterp spec fn is_unaligned<V>() -> bool;
+
+#[verifier::external_body]
+pub broadcast proof fn axiom_unaligned_align_of<V>()
+    requires
+        is_unaligned::<V>(),
+    ensures
+        #[trigger] align_of::<V>() == 1,
+;
+
+/// The byte representation of a `V` value, `size_of::<V>()` bytes long. Only meaningful
+/// when `is_as_bytes::<V>()` holds -- otherwise `V` may contain padding or uninitialized
+/// bytes that have no well-defined value.
+pub uninterp spec fn bytes_of<V>(v: V) -> Seq<u8>;
+
+#[verifier::external_body]
+pub broadcast proof fn axiom_bytes_of_len<V>(v: V)
+    ensures
+        #[trigger] bytes_of(v).len() == size_of::<V>(),
+;
+
+/// Reinterprets a byte sequence as a `V` value. Only sound to use when `is_from_bytes::<V>()`
+/// holds and `bytes.len() == size_of::<V>()`, since otherwise some bit patterns might not be
+/// valid `V` values.
+pub uninterp spec fn from_bytes<V>(bytes: Seq<u8>) -> V;
+
+/// Round-tripping through `bytes_of`/`from_bytes` recovers the original value, provided `V`
+/// has no padding (`is_as_bytes`) and every bit pattern is valid (`is_from_bytes`).
+#[verifier::external_body]
+pub broadcast proof fn axiom_from_bytes_bytes_of<V>(v: V)
+    requires
+        is_from_bytes::<V>(),
+        is_as_bytes::<V>(),
+    ensures
+        #[trigger] from_bytes::<V>(bytes_of(v)) == v,
+;
+
+/// The integer primitives are all `FromBytes`/`IntoBytes`; only byte-sized ones are also
+/// `Unaligned`.
+#[verifier::external_body]
+pub broadcast proof fn axiom_primitive_ints_zero_copy()
+    ensures
+        is_from_bytes::<u8>(),
+        is_as_bytes::<u8>(),
+        is_unaligned::<u8>(),
+        is_from_bytes::<i8>(),
+        is_as_bytes::<i8>(),
+        is_unaligned::<i8>(),
+        is_from_bytes::<u16>(),
+        is_as_bytes::<u16>(),
+        is_from_bytes::<i16>(),
+        is_as_bytes::<i16>(),
+        is_from_bytes::<u32>(),
+        is_as_bytes::<u32>(),
+        is_from_bytes::<i32>(),
+        is_as_bytes::<i32>(),
+        is_from_bytes::<u64>(),
+        is_as_bytes::<u64>(),
+        is_from_bytes::<i64>(),
+        is_as_bytes::<i64>(),
+        is_from_bytes::<usize>(),
+        is_as_bytes::<usize>(),
+        is_from_bytes::<isize>(),
+        is_as_bytes::<isize>(),
+;
+
+pub broadcast group group_zero_copy_axioms {
+    axiom_unaligned_align_of,
+    axiom_bytes_of_len,
+    axiom_from_bytes_bytes_of,
+    axiom_primitive_ints_zero_copy,
+}
+
+//////////////////////////////////////
+// size_of_val / align_of_val
+// Unlike `size_of`/`align_of`, these work for unsized `V` -- the dynamic size/alignment is
+// read off the fat-pointer metadata of `val` rather than `V` alone.
+pub uninterp spec fn size_of_val<V: ?Sized>(val: &V) -> nat;
+
+pub uninterp spec fn align_of_val<V: ?Sized>(val: &V) -> nat;
+
+#[verifier::inline]
+pub open spec fn size_of_val_as_usize<V: ?Sized>(val: &V) -> usize
+    recommends
+        size_of_val(val) as usize as int == size_of_val(val),
+{
+    size_of_val(val) as usize
+}
+
+#[verifier::inline]
+pub open spec fn align_of_val_as_usize<V: ?Sized>(val: &V) -> usize
+    recommends
+        align_of_val(val) as usize as int == align_of_val(val),
+{
+    align_of_val(val) as usize
+}
+
+#[verifier::when_used_as_spec(size_of_val_as_usize)]
+pub assume_specification<V: ?Sized>[ core::mem::size_of_val::<V> ](val: &V) -> (u: usize)
+    ensures
+        u as nat == size_of_val(val),
+    opens_invariants none
+    no_unwind
+;
+
+#[verifier::when_used_as_spec(align_of_val_as_usize)]
+pub assume_specification<V: ?Sized>[ core::mem::align_of_val::<V> ](val: &V) -> (u: usize)
+    ensures
+        u as nat == align_of_val(val),
+    opens_invariants none
+    no_unwind
+;
+
+/// For sized types, `size_of_val`/`align_of_val` agree with `size_of`/`align_of`.
+#[verifier::external_body]
+pub broadcast proof fn size_of_val_is_size_of<V>(val: &V)
+    requires
+        is_sized::<V>(),
+    ensures
+        #[trigger] size_of_val(val) == size_of::<V>(),
+        align_of_val(val) == align_of::<V>(),
+;
+
+/// For slices, the dynamic size is the element size times the slice's length, and the
+/// alignment is just the element's alignment.
+#[verifier::external_body]
+pub broadcast proof fn size_of_val_slice<T>(val: &[T])
+    ensures
+        #[trigger] size_of_val(val) == size_of::<T>() * val@.len(),
+        align_of_val(val) == align_of::<T>(),
+;
+
+pub broadcast group group_size_of_val_axioms {
+    size_of_val_is_size_of,
+    size_of_val_slice,
+}
+
 } // verus!
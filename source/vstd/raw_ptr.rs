@@ -18,6 +18,7 @@ they can be seamlessly cast to and fro.
 
 use super::layout::*;
 use super::prelude::*;
+use core::mem::MaybeUninit;
 use core::slice::SliceIndex;
 use core::ops::Index;
 use crate::vstd::slice::spec_slice_len;
@@ -95,9 +96,20 @@ pub ghost enum Metadata {
     Dyn(DynMetadata),
 }
 
+/// Metadata for a `dyn Trait` pointer, i.e. a vtable pointer.
 #[verifier::external_body]
 pub ghost struct DynMetadata {}
 
+impl DynMetadata {
+    /// Size in bytes of the concrete type behind this vtable
+    /// (what `core::ptr::DynMetadata::size_of` returns at runtime).
+    pub uninterp spec fn size_of(self) -> usize;
+
+    /// Alignment in bytes of the concrete type behind this vtable
+    /// (what `core::ptr::DynMetadata::align_of` returns at runtime).
+    pub uninterp spec fn align_of(self) -> usize;
+}
+
 /// Model of a pointer `*mut T` or `*const T` in Rust's abstract machine
 pub ghost struct PtrData {
     pub addr: usize,
@@ -244,6 +256,59 @@ impl<T> PointsTo<T> {
 
 impl<T: ?Sized> PointsTo<T> {
     pub uninterp spec fn ptr(&self) -> *mut T;
+
+    /// Size in bytes of the pointee covered by this `PointsTo`: `size_of::<T>()` for thin
+    /// pointers, or the vtable-reported size for `dyn` pointers.
+    ///
+    /// Meaningless for a slice/str pointer (`Metadata::Length`): those track their size via
+    /// `mem_contents_seq()` instead (see `PointsTo<[T]>`), and `size_of::<T>()` isn't even a
+    /// sound stand-in for an unsized `T`, so this arm is `arbitrary()` rather than a value a
+    /// caller might mistake for meaningful. `ptr_bounds_metadata`/`is_disjoint_metadata` below
+    /// `requires` metadata to be `Thin` or `Dyn` precisely to keep callers from reaching it.
+    pub open spec fn metadata_size(&self) -> usize {
+        match self.ptr()@.metadata {
+            Metadata::Dyn(d) => d.size_of(),
+            Metadata::Thin => size_of::<T>() as usize,
+            Metadata::Length(_) => arbitrary(),
+        }
+    }
+
+    /// Like `ptr_bounds`, but also covers `dyn` pointers by reading the pointee's size
+    /// off the vtable instead of assuming `size_of::<T>()`.
+    ///
+    /// Excludes `Metadata::Length` (slice/str pointers): `metadata_size()` doesn't have a
+    /// meaningful value there, so the bound this would prove would be vacuous or wrong. Use
+    /// `PointsTo<[T]>`'s own accessors (`mem_contents_seq().len()`) for those instead.
+    #[verifier::external_body]
+    pub proof fn ptr_bounds_metadata(tracked &self)
+        requires
+            self.ptr()@.metadata is Dyn || self.ptr()@.metadata is Thin,
+            self.metadata_size() != 0,
+        ensures
+            self.ptr()@.provenance.start_addr() <= self.ptr()@.addr,
+            self.ptr()@.addr + self.metadata_size() <= self.ptr()@.provenance.start_addr()
+                + self.ptr()@.provenance.alloc_len(),
+    {
+        unimplemented!();
+    }
+
+    /// Like `is_disjoint`, but also covers `dyn` pointers by reading each pointee's size
+    /// off the vtable instead of assuming `size_of::<T>()`/`size_of::<S>()`.
+    ///
+    /// Excludes `Metadata::Length` (slice/str pointers) on either side, for the same reason as
+    /// `ptr_bounds_metadata`.
+    #[verifier::external_body]
+    pub proof fn is_disjoint_metadata<S: ?Sized>(tracked &mut self, tracked other: &PointsTo<S>)
+        requires
+            old(self).ptr()@.metadata is Dyn || old(self).ptr()@.metadata is Thin,
+            other.ptr()@.metadata is Dyn || other.ptr()@.metadata is Thin,
+        ensures
+            *old(self) == *self,
+            self.ptr()@.addr + self.metadata_size() <= other.ptr()@.addr || other.ptr()@.addr
+                + other.metadata_size() <= self.ptr()@.addr,
+    {
+        unimplemented!();
+    }
 }
 
 impl<T> PointsTo<[T]> {
@@ -295,8 +360,16 @@ impl<T> PointsTo<[T]> {
         unimplemented!();
     }
 
-    // TODO: Add invariant that self.ptr()@.metadata == Metadata::Length(self.mem_contents_seq().len())?
-    // Probably skip unless I need it
+    /// A `PointsTo<[T]>`'s pointer always carries a `Metadata::Length` matching the length
+    /// of the sequence it points to -- this is what lets `from_raw_parts`'s
+    /// `Metadata::Length(n)` be trusted to produce a `PointsTo<[T]>` with
+    /// `mem_contents_seq().len() == n`.
+    #[verifier::external_body]
+    pub broadcast proof fn axiom_points_to_slice_metadata_len(tracked &self)
+        ensures
+            #[trigger] self.ptr()@.metadata == Metadata::Length(self.mem_contents_seq().len() as usize),
+    {
+    }
 
     #[verifier::external_body]
     pub proof fn subrange(tracked &self, start_index: usize, len: nat) -> (tracked sub_points_to: &Self)
@@ -380,6 +453,90 @@ impl<T> MemContents<T> {
     }
 }
 
+//////////////////////////////////////
+// MaybeUninit bridge
+// `core::mem::MaybeUninit<T>` is exactly `MemContents<T>` in spirit -- either uninitialized,
+// or initialized with a `T`. Rather than treat `MaybeUninit` as opaque, tie its view directly
+// to `MemContents`, so `PointsTo<T>` and `PointsTo<MaybeUninit<T>>` can be freely converted.
+impl<T> View for MaybeUninit<T> {
+    type V = MemContents<T>;
+
+    uninterp spec fn view(&self) -> Self::V;
+}
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification<T>[ MaybeUninit::<T>::uninit ]() -> (res: MaybeUninit<T>)
+    ensures
+        res@ == MemContents::<T>::Uninit,
+    opens_invariants none
+    no_unwind
+;
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification<T>[ MaybeUninit::<T>::new ](val: T) -> (res: MaybeUninit<T>)
+    ensures
+        res@ == MemContents::Init(val),
+    opens_invariants none
+    no_unwind
+;
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification<T>[ MaybeUninit::<T>::write ](
+    m: &mut MaybeUninit<T>,
+    val: T,
+) -> (r: &mut T)
+    ensures
+        m@ == MemContents::Init(val),
+        *r == val,
+    opens_invariants none
+    no_unwind
+;
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification<T>[ MaybeUninit::<T>::assume_init ](m: MaybeUninit<T>) -> (val: T)
+    requires
+        m@.is_init(),
+    ensures
+        val == m@.value(),
+    opens_invariants none
+    no_unwind
+;
+
+impl<T> PointsTo<T> {
+    /// Reinterprets a `PointsTo<T>` as a `PointsTo<MaybeUninit<T>>`, carrying the same
+    /// `MemContents` across the bridge. `MaybeUninit<T>` and `T` have identical layout, so
+    /// this is a ghost-state relabeling, not a real operation.
+    #[verifier::external_body]
+    pub proof fn into_maybe_uninit(tracked self) -> (tracked points_to: PointsTo<MaybeUninit<T>>)
+        ensures
+            points_to.ptr() == ptr_mut_from_data::<MaybeUninit<T>>(
+                PtrData { addr: self.ptr()@.addr, provenance: self.ptr()@.provenance, metadata: Metadata::Thin },
+            ),
+            points_to.is_init(),
+            points_to.value()@ == self.opt_value(),
+    {
+        unimplemented!();
+    }
+}
+
+impl<T> PointsTo<MaybeUninit<T>> {
+    /// Inverse of `into_maybe_uninit`. `self` must already hold *some* `MaybeUninit<T>`
+    /// value (`is_init()`); that value's own view may still be `MemContents::Uninit`, which
+    /// carries through to the resulting `PointsTo<T>`.
+    #[verifier::external_body]
+    pub proof fn from_maybe_uninit(tracked self) -> (tracked points_to: PointsTo<T>)
+        requires
+            self.is_init(),
+        ensures
+            points_to.ptr() == ptr_mut_from_data::<T>(
+                PtrData { addr: self.ptr()@.addr, provenance: self.ptr()@.provenance, metadata: Metadata::Thin },
+            ),
+            points_to.opt_value() == self.value()@,
+    {
+        unimplemented!();
+    }
+}
+
 //////////////////////////////////////
 // Inverse functions:
 // Pointers are equivalent to their model
@@ -446,6 +603,44 @@ pub assume_specification<
     no_unwind
 ;
 
+//////////////////////////////////////
+// Strict-provenance sentinel pointers
+// Like null pointers, these carry no provenance; unlike null, the address need not be 0.
+// See: <https://doc.rust-lang.org/std/ptr/fn.without_provenance.html>
+#[verifier::inline]
+pub open spec fn ptr_invalid<T: ?Sized + core::ptr::Pointee<Metadata = ()>>(addr: usize) -> *const T {
+    ptr_from_data(PtrData { addr: addr, provenance: Provenance::null(), metadata: Metadata::Thin })
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(ptr_invalid)]
+pub assume_specification<
+    T: ?Sized + core::ptr::Pointee<Metadata = ()>,
+>[ core::ptr::without_provenance::<T> ](addr: usize) -> (res: *const T)
+    ensures
+        res == ptr_invalid::<T>(addr),
+    opens_invariants none
+    no_unwind
+;
+
+#[verifier::inline]
+pub open spec fn ptr_invalid_mut<T: ?Sized + core::ptr::Pointee<Metadata = ()>>(
+    addr: usize,
+) -> *mut T {
+    ptr_mut_from_data(PtrData { addr: addr, provenance: Provenance::null(), metadata: Metadata::Thin })
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(ptr_invalid_mut)]
+pub assume_specification<
+    T: ?Sized + core::ptr::Pointee<Metadata = ()>,
+>[ core::ptr::without_provenance_mut::<T> ](addr: usize) -> (res: *mut T)
+    ensures
+        res == ptr_invalid_mut::<T>(addr),
+    opens_invariants none
+    no_unwind
+;
+
 //////////////////////////////////////
 // Casting
 // as-casts and implicit casts are translated internally to these functions
@@ -505,6 +700,122 @@ pub fn cast_ptr_to_usize<T: Sized>(ptr: *mut T) -> (result: usize)
     ptr as usize
 }
 
+//////////////////////////////////////
+// Metadata and from_raw_parts
+// (RFC 2580: https://rust-lang.github.io/rfcs/2580-ptr-meta.html)
+//
+// `core::ptr::{metadata, from_raw_parts, from_raw_parts_mut}` are generic over
+// `<T as Pointee>::Metadata`, which is `()` for sized types, `usize` for slices and `str`,
+// or `DynMetadata<Dyn>` for trait objects. VIR can't yet match on that associated type the
+// way rustc does (see `Metadata`'s doc comment above), so -- as with `ptr_null` above --
+// we hook up one `assume_specification` per concrete `Metadata` bound instead of a single
+// fully-generic one.
+//
+// No `assume_specification` is given here for the `DynMetadata` case.
+
+pub open spec fn spec_metadata<T: ?Sized>(ptr: *const T) -> Metadata {
+    ptr@.metadata
+}
+
+#[cfg(verus_keep_ghost)]
+pub assume_specification<T: ?Sized + core::ptr::Pointee<Metadata = ()>>[ core::ptr::metadata::<T> ](
+    ptr: *const T,
+) -> (meta: ())
+    ensures
+        spec_metadata(ptr) == Metadata::Thin,
+    opens_invariants none
+    no_unwind
+;
+
+pub open spec fn spec_metadata_len<T: ?Sized>(ptr: *const T) -> usize
+    recommends
+        spec_metadata(ptr) is Length,
+{
+    spec_metadata(ptr)->Length_0
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(spec_metadata_len)]
+pub assume_specification<T: ?Sized + core::ptr::Pointee<Metadata = usize>>[ core::ptr::metadata::<T> ](
+    ptr: *const T,
+) -> (meta: usize)
+    ensures
+        spec_metadata(ptr) == Metadata::Length(meta),
+    opens_invariants none
+    no_unwind
+;
+
+pub open spec fn spec_from_raw_parts_thin<T: Sized>(data: *const ()) -> *const T {
+    ptr_from_data(
+        PtrData { addr: data@.addr, provenance: data@.provenance, metadata: Metadata::Thin },
+    )
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(spec_from_raw_parts_thin)]
+pub assume_specification<T: Sized>[ core::ptr::from_raw_parts::<T> ](
+    data: *const (),
+    metadata: (),
+) -> (p: *const T)
+    ensures
+        p == spec_from_raw_parts_thin(data),
+    opens_invariants none
+    no_unwind
+;
+
+pub open spec fn spec_from_raw_parts_with_len<T: ?Sized>(data: *const (), len: usize) -> *const T {
+    ptr_from_data(
+        PtrData { addr: data@.addr, provenance: data@.provenance, metadata: Metadata::Length(len) },
+    )
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(spec_from_raw_parts_with_len)]
+pub assume_specification<T: ?Sized + core::ptr::Pointee<Metadata = usize>>[ core::ptr::from_raw_parts::<T> ](
+    data: *const (),
+    metadata: usize,
+) -> (p: *const T)
+    ensures
+        p == spec_from_raw_parts_with_len(data, metadata),
+    opens_invariants none
+    no_unwind
+;
+
+pub open spec fn spec_from_raw_parts_mut_thin<T: Sized>(data: *mut ()) -> *mut T {
+    ptr_mut_from_data(
+        PtrData { addr: data@.addr, provenance: data@.provenance, metadata: Metadata::Thin },
+    )
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(spec_from_raw_parts_mut_thin)]
+pub assume_specification<T: Sized>[ core::ptr::from_raw_parts_mut::<T> ](
+    data: *mut (),
+    metadata: (),
+) -> (p: *mut T)
+    ensures
+        p == spec_from_raw_parts_mut_thin(data),
+    opens_invariants none
+    no_unwind
+;
+
+pub open spec fn spec_from_raw_parts_mut_with_len<T: ?Sized>(data: *mut (), len: usize) -> *mut T {
+    ptr_mut_from_data(
+        PtrData { addr: data@.addr, provenance: data@.provenance, metadata: Metadata::Length(len) },
+    )
+}
+
+#[cfg(verus_keep_ghost)]
+#[verifier::when_used_as_spec(spec_from_raw_parts_mut_with_len)]
+pub assume_specification<
+    T: ?Sized + core::ptr::Pointee<Metadata = usize>,
+>[ core::ptr::from_raw_parts_mut::<T> ](data: *mut (), metadata: usize) -> (p: *mut T)
+    ensures
+        p == spec_from_raw_parts_mut_with_len(data, metadata),
+    opens_invariants none
+    no_unwind
+;
+
 //////////////////////////////////////
 // Reading and writing
 /// Calls `core::ptr::write`
@@ -532,8 +843,7 @@ pub fn ptr_mut_write<T>(ptr: *mut T, Tracked(perm): Tracked<&mut PointsTo<T>>, v
 ///
 /// This leaves the data as "unitialized", i.e., performs a move.
 ///
-/// TODO This needs to be made more general (i.e., should be able to read a Copy type
-/// without destroying it; should be able to leave the bytes intact without uninitializing them)
+/// See [`ptr_read_copy`] to read a `Copy` type without destroying it.
 #[inline(always)]
 #[verifier::external_body]
 pub fn ptr_mut_read<T>(ptr: *const T, Tracked(perm): Tracked<&mut PointsTo<T>>) -> (v: T)
@@ -550,6 +860,23 @@ pub fn ptr_mut_read<T>(ptr: *const T, Tracked(perm): Tracked<&mut PointsTo<T>>)
     unsafe { core::ptr::read(ptr) }
 }
 
+/// Like [`ptr_mut_read`], but for `T: Copy` types: since reading a `Copy` value can't
+/// invalidate the original, this takes `perm` by shared reference and leaves the memory
+/// initialized with the same value, instead of consuming `perm` and uninitializing it.
+#[inline(always)]
+#[verifier::external_body]
+pub fn ptr_read_copy<T: Copy>(ptr: *const T, Tracked(perm): Tracked<&PointsTo<T>>) -> (v: T)
+    requires
+        perm.ptr() == ptr,
+        perm.is_init(),
+    ensures
+        v == perm.value(),
+    opens_invariants none
+    no_unwind
+{
+    unsafe { core::ptr::read(ptr) }
+}
+
 /// equivalent to &*X
 #[inline(always)]
 #[verifier::external_body]
@@ -659,14 +986,16 @@ impl IsExposed {
 }
 
 /// Perform a provenance expose operation.
+/// Unlike `with_exposed_provenance` below, this works for any pointer, fat or thin,
+/// since exposing provenance only needs the address, not the metadata.
 #[verifier::external_body]
-pub fn expose_provenance<T: Sized>(m: *mut T) -> (provenance: Tracked<IsExposed>)
+pub fn expose_provenance<T: ?Sized>(m: *mut T) -> (provenance: Tracked<IsExposed>)
     ensures
         provenance@@ == m@.provenance,
     opens_invariants none
     no_unwind
 {
-    let _ = m as usize;
+    let _ = m.addr();
     Tracked::assume_new()
 }
 
@@ -688,6 +1017,24 @@ pub fn with_exposed_provenance<T: Sized>(
     addr as *mut T
 }
 
+/// Like `with_exposed_provenance`, but for slice pointers: the provenance must have
+/// previously been exposed, and the resulting pointer carries `len` as its `Metadata::Length`.
+#[verifier::external_body]
+pub fn with_exposed_provenance_len<T>(
+    addr: usize,
+    len: usize,
+    Tracked(provenance): Tracked<IsExposed>,
+) -> (p: *mut [T])
+    ensures
+        p == ptr_mut_from_data::<[T]>(
+            PtrData { addr: addr, provenance: provenance@, metadata: Metadata::Length(len) },
+        ),
+    opens_invariants none
+    no_unwind
+{
+    core::ptr::slice_from_raw_parts_mut(addr as *mut T, len)
+}
+
 /// PointsToRaw
 /// Variable-sized uninitialized memory.
 ///
@@ -715,10 +1062,31 @@ impl PointsToRaw {
         super::set_lib::set_int_range(start, start + len) <= self.dom()
     }
 
+    /// Addresses within `dom()` whose bytes are currently initialized.
+    /// An `empty` region has no addresses initialized; `split`/`join` carry the mask along
+    /// with the domain, and `from_typed` below produces a fully initialized range.
+    pub uninterp spec fn init_mask(self) -> Set<int>;
+
+    /// Lossless view of the bytes at the initialized addresses; only meaningful for
+    /// addresses in `init_mask()`.
+    pub uninterp spec fn bytes(self) -> Map<int, u8>;
+
+    /// Well-formedness facts relating `init_mask`/`bytes` to `dom`, always true of any
+    /// `PointsToRaw`.
+    #[verifier::external_body]
+    pub proof fn init_mask_wf(tracked &self)
+        ensures
+            self.init_mask().subset_of(self.dom()),
+            self.bytes().dom() == self.init_mask(),
+    {
+        unimplemented!();
+    }
+
     #[verifier::external_body]
     pub proof fn empty(provenance: Provenance) -> (tracked points_to_raw: Self)
         ensures
             points_to_raw.dom() == Set::<int>::empty(),
+            points_to_raw.init_mask() == Set::<int>::empty(),
             points_to_raw.provenance() == provenance,
     {
         unimplemented!();
@@ -733,6 +1101,10 @@ impl PointsToRaw {
             res.1.provenance() == self.provenance(),
             res.0.dom() == range,
             res.1.dom() == self.dom().difference(range),
+            res.0.init_mask() == self.init_mask().intersect(range),
+            res.1.init_mask() == self.init_mask().intersect(self.dom().difference(range)),
+            res.0.bytes() == self.bytes().restrict(range),
+            res.1.bytes() == self.bytes().restrict(self.dom().difference(range)),
     {
         unimplemented!();
     }
@@ -744,6 +1116,8 @@ impl PointsToRaw {
         ensures
             joined.provenance() == self.provenance(),
             joined.dom() == self.dom() + other.dom(),
+            joined.init_mask() == self.init_mask() + other.init_mask(),
+            joined.bytes() == self.bytes().union_prefer_right(other.bytes()),
     {
         unimplemented!();
     }
@@ -768,6 +1142,22 @@ impl PointsToRaw {
     {
         unimplemented!();
     }
+
+    /// Inverse of `into_typed`: converts an initialized `PointsTo<V>` back into a
+    /// `PointsToRaw` whose `init_mask` covers the whole range. The ensures do not relate the
+    /// resulting `bytes()` to `points_to.value()`.
+    #[verifier::external_body]
+    pub proof fn from_typed<V>(tracked points_to: PointsTo<V>) -> (tracked points_to_raw: Self)
+        requires
+            points_to.is_init(),
+        ensures
+            points_to_raw.is_range(points_to.ptr().addr() as int, size_of::<V>() as int),
+            points_to_raw.init_mask() == points_to_raw.dom(),
+            points_to_raw.provenance() == points_to.ptr()@.provenance,
+            is_sized::<V>(),
+    {
+        unimplemented!();
+    }
 }
 
 impl<V> PointsTo<V> {